@@ -0,0 +1,100 @@
+// The showoff game, shared by the showoff example in a window and the tty example in a text terminal.
+use std::path::Path;
+
+use rand::seq::IteratorRandom;
+use rand::thread_rng;
+
+use ooze::animation::Easing;
+use ooze::app::GameState;
+use ooze::error::OozeResult;
+use ooze::frame::Frame;
+use ooze::geometry::Point;
+use ooze::terminal::{Panel, Terminal};
+
+// Load the panel tree: the room, and a panel above that for the ooze
+pub fn setup(terminal: &mut Terminal) -> OozeResult<()> {
+    terminal.root_panel =
+        Panel::load(&Path::new("resources").join("layouts").join("showoff.toml"))?;
+
+    // Use root_panel for walls and floors
+    terminal
+        .root_panel
+        .fill_with("floor", [0.07, 0.04, 0.06, 1.0], [0.0, 0.0, 0.0, 1.0])?;
+    terminal
+        .root_panel
+        .make_border("wall", [0.09, 0.03, 0.04, 1.0], [0.0, 0.0, 0.0, 1.0])?;
+
+    terminal.panel_mut("ooze").unwrap().place(
+        6,
+        2,
+        "ooze",
+        [0.0, 1.0, 0.0, 1.0],
+        [0.0, 0.0, 0.0, 0.0],
+    )
+}
+
+// Moves the slime around randomly within the room, sliding it between cells
+pub fn update_game(frame: &mut Frame, game_state: &mut MyGameState) {
+    let old_pos = Point::new(game_state.pos[0], game_state.pos[1]);
+    frame
+        .terminal
+        .panel_mut("ooze")
+        .unwrap()
+        .place(
+            game_state.pos[0],
+            game_state.pos[1],
+            "empty",
+            [0.0, 0.0, 0.0, 0.0],
+            [0.0, 0.0, 0.0, 0.0],
+        )
+        .unwrap();
+    game_state.update();
+    frame
+        .terminal
+        .panel_mut("ooze")
+        .unwrap()
+        .place(
+            game_state.pos[0],
+            game_state.pos[1],
+            "ooze",
+            [0.0, 1.0, 0.0, 1.0],
+            [0.0, 0.0, 0.0, 0.0],
+        )
+        .unwrap();
+
+    let new_pos = Point::new(game_state.pos[0], game_state.pos[1]);
+    if new_pos != old_pos {
+        let ooze = frame.terminal.panel("ooze").unwrap().id();
+        frame
+            .animator
+            .slide_glyph(ooze, new_pos, old_pos, 0.1, Easing::OutQuad);
+    }
+}
+
+// Holds the position of the ooze
+pub struct MyGameState {
+    pub pos: [u32; 2],
+}
+
+impl GameState for MyGameState {
+    // is called by the app's update_game callback. Moves the ooze randomly.
+    fn update(&mut self) {
+        let dirs: [i32; 3] = [-1, 0, 1];
+        let mut x_dir = *dirs.iter().choose(&mut thread_rng()).unwrap();
+        let mut y_dir = *dirs.iter().choose(&mut thread_rng()).unwrap();
+
+        if self.pos[0] as i32 + x_dir >= 8 || self.pos[0] as i32 + x_dir <= 1 {
+            x_dir = 0;
+        }
+        if self.pos[1] as i32 + y_dir >= 8 || self.pos[1] as i32 + y_dir <= 1 {
+            y_dir = 0;
+        }
+
+        let new_pos = [
+            (x_dir + self.pos[0] as i32) as u32,
+            (y_dir + self.pos[1] as i32) as u32,
+        ];
+
+        self.pos = new_pos;
+    }
+}
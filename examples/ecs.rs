@@ -0,0 +1,94 @@
+use std::path::Path;
+
+use rand::seq::IteratorRandom;
+use rand::thread_rng;
+use specs::{Builder, Join, World, WorldExt};
+
+use ooze;
+use ooze::app::*;
+use ooze::ecs::*;
+use ooze::error::*;
+use ooze::frame::Frame;
+use ooze::geometry::*;
+use ooze::terminal::Panel;
+
+fn main() -> OozeResult<()> {
+    // App initialize
+    let mut app = App::new(
+        Dimensions::new(16, 16, 50, 30, 0, 0),
+        1.0,
+        "ECS",
+        &Path::new("resources").join("sheets").join("showoff.png"),
+    )
+    .unwrap();
+
+    // Load the panel tree: the room, and a panel above that for the entities
+    app.terminal.root_panel =
+        Panel::load(&Path::new("resources").join("layouts").join("showoff.toml"))?;
+    app.terminal
+        .root_panel
+        .fill_with("floor", [0.07, 0.04, 0.06, 1.0], [0.0, 0.0, 0.0, 1.0])
+        .unwrap();
+    app.terminal
+        .root_panel
+        .make_border("wall", [0.09, 0.03, 0.04, 1.0], [0.0, 0.0, 0.0, 1.0])
+        .unwrap();
+
+    // A few oozes, the green one is drawn over the others when they meet
+    let mut game_state = MyGameState {
+        world: World::new(),
+    };
+    register_components(&mut game_state.world);
+    let oozes = [
+        ([0.0, 1.0, 0.0, 1.0], 1),
+        ([0.6, 0.2, 0.8, 1.0], 0),
+        ([0.9, 0.6, 0.1, 1.0], 0),
+    ];
+    for (i, (color, render_order)) in oozes.iter().enumerate() {
+        game_state
+            .world
+            .create_entity()
+            .with(Position::new(3 + i as u32 * 2, 4))
+            .with(
+                Renderable::new("ooze", *color, [0.0, 0.0, 0.0, 0.0])
+                    .with_render_order(*render_order),
+            )
+            .build();
+    }
+
+    // Use our update function
+    app.update_game_callback = update_game;
+
+    // Start the application loop
+    app.run(&mut game_state)
+}
+
+// Moves the oozes, then draws them without any erasing or placing by hand
+fn update_game(frame: &mut Frame, game_state: &mut MyGameState) {
+    game_state.update();
+    render_world(&game_state.world, frame.terminal.panel_mut("ooze").unwrap()).unwrap();
+}
+
+// Holds the entities
+struct MyGameState {
+    world: World,
+}
+
+impl GameState for MyGameState {
+    // is called by the app's update_game callback. Moves every ooze randomly within the room.
+    fn update(&mut self) {
+        let mut positions = self.world.write_storage::<Position>();
+        for position in (&mut positions).join() {
+            let dirs: [i32; 3] = [-1, 0, 1];
+            let x = position.x as i32 + *dirs.iter().choose(&mut thread_rng()).unwrap();
+            let y = position.y as i32 + *dirs.iter().choose(&mut thread_rng()).unwrap();
+
+            if x > 0 && x < 9 {
+                position.x = x as u32;
+            }
+            if y > 0 && y < 9 {
+                position.y = y as u32;
+            }
+        }
+    }
+}
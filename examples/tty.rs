@@ -0,0 +1,30 @@
+use ooze::error::*;
+use ooze::geometry::*;
+use ooze::tty::TtyApp;
+
+mod common;
+use common::{setup, update_game, MyGameState};
+
+fn main() -> OozeResult<()> {
+    // Only the terminal size matters in a text terminal
+    let mut app = TtyApp::new(Dimensions::new(1, 1, 50, 30, 0, 0));
+    let mut game_state = MyGameState { pos: [6, 2] };
+
+    // The same sprite ids as the showoff example, shown as characters
+    app.to_char = to_char;
+
+    // The showoff game, escape or ctrl-c quits
+    setup(&mut app.terminal)?;
+    app.update_game_callback = update_game;
+
+    app.run(&mut game_state)
+}
+
+fn to_char(sprite_id: &str) -> char {
+    match sprite_id {
+        "floor" => '.',
+        "wall" => '#',
+        "ooze" => '@',
+        _ => ' ',
+    }
+}
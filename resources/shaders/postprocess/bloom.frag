@@ -0,0 +1,24 @@
+#version 140
+
+in vec2 v_tex_coords;
+
+out vec4 color;
+
+uniform sampler2D tex;
+uniform vec2 screen_size;
+uniform float threshold;
+uniform float intensity;
+
+void main() {
+    vec4 source = texture(tex, v_tex_coords);
+    vec2 pixel = 1.0 / screen_size;
+    vec3 glow = vec3(0.0);
+    for (int x = -2; x <= 2; x++) {
+        for (int y = -2; y <= 2; y++) {
+            vec3 sample_color = texture(tex, v_tex_coords + vec2(x, y) * pixel * 2.0).rgb;
+            float brightness = max(sample_color.r, max(sample_color.g, sample_color.b));
+            glow += sample_color * step(threshold, brightness);
+        }
+    }
+    color = vec4(source.rgb + glow / 25.0 * intensity, source.a);
+}
@@ -0,0 +1,19 @@
+#version 140
+
+in vec2 v_tex_coords;
+
+out vec4 color;
+
+uniform sampler2D tex;
+uniform float brightness;
+uniform float contrast;
+uniform float saturation;
+uniform vec3 tint;
+
+void main() {
+    vec4 source = texture(tex, v_tex_coords);
+    vec3 graded = (source.rgb - 0.5) * contrast + 0.5 + brightness;
+    float luma = dot(graded, vec3(0.299, 0.587, 0.114));
+    graded = mix(vec3(luma), graded, saturation) * tint;
+    color = vec4(clamp(graded, 0.0, 1.0), source.a);
+}
@@ -0,0 +1,18 @@
+#version 140
+
+in vec2 v_tex_coords;
+
+out vec4 color;
+
+uniform sampler2D tex;
+uniform vec2 screen_size;
+uniform float time;
+uniform float intensity;
+
+void main() {
+    vec4 source = texture(tex, v_tex_coords);
+    float row = floor(v_tex_coords.y * screen_size.y);
+    float scanline = mod(row, 2.0) < 1.0 ? 1.0 - intensity : 1.0;
+    float flicker = 1.0 - intensity * 0.05 * sin(time * 60.0);
+    color = vec4(source.rgb * scanline * flicker, source.a);
+}
@@ -0,0 +1,16 @@
+#version 140
+
+in vec2 v_tex_coords;
+
+out vec4 color;
+
+uniform sampler2D tex;
+uniform float radius;
+uniform float softness;
+
+void main() {
+    vec4 source = texture(tex, v_tex_coords);
+    float dist = distance(v_tex_coords, vec2(0.5, 0.5));
+    float vignette = smoothstep(radius, radius - softness, dist);
+    color = vec4(source.rgb * vignette, source.a);
+}
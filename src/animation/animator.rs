@@ -0,0 +1,465 @@
+use crate::animation::{Easing, Tween};
+use crate::geometry::Point;
+use crate::terminal::{Glyph, GlyphEffects, Panel, PanelId, Terminal};
+
+/// What an Animation changes: one Glyph of a Panel, or every Glyph of a Panel and its sub-panels.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Target {
+    Glyph(PanelId, Point),
+    Panel(PanelId),
+}
+
+/// What an Animation does to the glyphs of its target.
+/// Color changes stay when the Animation ends, the other effects only last while it runs.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Effect {
+    /// Shift the glyphs from their cells, in cells.
+    Offset(Tween<[f32; 2]>),
+    /// Change the foreground color.
+    FgColor(Tween<[f32; 4]>),
+    /// Change the background color.
+    BgColor(Tween<[f32; 4]>),
+    /// Multiply the alpha of both colors.
+    Opacity(Tween<f32>),
+    /// Blend both colors towards a color by a changing amount.
+    Tint([f32; 3], Tween<f32>),
+    /// Jitter the glyphs randomly by up to this many cells.
+    Shake(Tween<f32>),
+}
+
+impl Effect {
+    fn set_start(&mut self, start: f32) {
+        match self {
+            Effect::Offset(tween) => tween.start = start,
+            Effect::FgColor(tween) | Effect::BgColor(tween) => tween.start = start,
+            Effect::Opacity(tween) | Effect::Tint(_, tween) | Effect::Shake(tween) => {
+                tween.start = start
+            }
+        }
+    }
+
+    fn finished(&self, time: f32) -> bool {
+        match self {
+            Effect::Offset(tween) => tween.finished(time),
+            Effect::FgColor(tween) | Effect::BgColor(tween) => tween.finished(time),
+            Effect::Opacity(tween) | Effect::Tint(_, tween) | Effect::Shake(tween) => {
+                tween.finished(time)
+            }
+        }
+    }
+}
+
+/// An Effect on a Target, optionally showing a Panel target when it starts or hiding it when it ends.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Animation {
+    pub target: Target,
+    pub effect: Effect,
+    pub show_at_start: bool,
+    pub hide_at_end: bool,
+}
+
+impl Animation {
+    /// Create a new Animation that doesn't show or hide its target.
+    pub fn new(target: Target, effect: Effect) -> Animation {
+        Animation {
+            target,
+            effect,
+            show_at_start: false,
+            hide_at_end: false,
+        }
+    }
+}
+
+/// Runs Animations on the glyphs of a Terminal's Panel tree, driven by the App clock.
+/// Effects are worked out again every frame, so glyphs the game changes while they are animated keep being animated.
+pub struct Animator {
+    pub animations: Vec<Animation>,
+
+    now: f32,
+    touched: Vec<Target>,
+}
+
+impl Animator {
+    /// Create a new Animator with nothing running.
+    pub fn new() -> Animator {
+        Animator {
+            animations: Vec::new(),
+            now: 0.0,
+            touched: Vec::new(),
+        }
+    }
+
+    /// The time of the last update, in seconds on the App clock.
+    pub fn now(&self) -> f32 {
+        self.now
+    }
+
+    /// Start an Animation at the time of the last update.
+    pub fn add(&mut self, animation: Animation) {
+        let mut animation = animation;
+        animation.effect.set_start(self.now);
+        self.animations.push(animation);
+    }
+
+    /// Check if any Animation is running, or one on the given Panel or its glyphs.
+    pub fn is_animating(&self, panel: Option<PanelId>) -> bool {
+        match panel {
+            None => !self.animations.is_empty(),
+            Some(id) => self
+                .animations
+                .iter()
+                .any(|animation| match animation.target {
+                    Target::Glyph(panel, _) | Target::Panel(panel) => panel == id,
+                }),
+        }
+    }
+
+    /// Stop every Animation. Their effects are removed on the next update.
+    pub fn clear(&mut self) {
+        self.animations.clear();
+    }
+
+    /// Draw the Glyph at location as if it slid there from another cell, for moving things without jumping.
+    pub fn slide_glyph(
+        &mut self,
+        panel: PanelId,
+        location: Point,
+        from: Point,
+        duration: f32,
+        easing: Easing,
+    ) {
+        let start = [
+            from.x as f32 - location.x as f32,
+            from.y as f32 - location.y as f32,
+        ];
+        let tween = Tween::new(start, [0.0, 0.0], duration, easing);
+        self.add(Animation::new(
+            Target::Glyph(panel, location),
+            Effect::Offset(tween),
+        ));
+    }
+
+    /// Change the foreground color of the Glyph at location.
+    pub fn tween_fg_color(
+        &mut self,
+        panel: PanelId,
+        location: Point,
+        from: [f32; 4],
+        to: [f32; 4],
+        duration: f32,
+        easing: Easing,
+    ) {
+        let tween = Tween::new(from, to, duration, easing);
+        self.add(Animation::new(
+            Target::Glyph(panel, location),
+            Effect::FgColor(tween),
+        ));
+    }
+
+    /// Change the background color of the Glyph at location.
+    pub fn tween_bg_color(
+        &mut self,
+        panel: PanelId,
+        location: Point,
+        from: [f32; 4],
+        to: [f32; 4],
+        duration: f32,
+        easing: Easing,
+    ) {
+        let tween = Tween::new(from, to, duration, easing);
+        self.add(Animation::new(
+            Target::Glyph(panel, location),
+            Effect::BgColor(tween),
+        ));
+    }
+
+    /// Flash the Glyph at location in a color that fades back out, like when something is hit.
+    /// The alpha of the color is how strong the flash starts.
+    pub fn flash_glyph(&mut self, panel: PanelId, location: Point, color: [f32; 4], duration: f32) {
+        self.add(flash(Target::Glyph(panel, location), color, duration));
+    }
+
+    /// Flash a whole Panel and its sub-panels, see Animator::flash_glyph.
+    pub fn flash_panel(&mut self, panel: PanelId, color: [f32; 4], duration: f32) {
+        self.add(flash(Target::Panel(panel), color, duration));
+    }
+
+    /// Show a Panel and fade it and its sub-panels in from invisible.
+    pub fn fade_in_panel(&mut self, panel: PanelId, duration: f32, easing: Easing) {
+        let mut animation = Animation::new(
+            Target::Panel(panel),
+            Effect::Opacity(Tween::new(0.0, 1.0, duration, easing)),
+        );
+        animation.show_at_start = true;
+        self.add(animation);
+    }
+
+    /// Fade a Panel and its sub-panels out, then hide it.
+    pub fn fade_out_panel(&mut self, panel: PanelId, duration: f32, easing: Easing) {
+        let mut animation = Animation::new(
+            Target::Panel(panel),
+            Effect::Opacity(Tween::new(1.0, 0.0, duration, easing)),
+        );
+        animation.hide_at_end = true;
+        self.add(animation);
+    }
+
+    /// Shake a Panel and its sub-panels by up to strength cells, calming down over the duration.
+    pub fn shake_panel(&mut self, panel: PanelId, strength: f32, duration: f32) {
+        let tween = Tween::new(strength, 0.0, duration, Easing::OutQuad);
+        self.add(Animation::new(Target::Panel(panel), Effect::Shake(tween)));
+    }
+
+    /// Apply every running Animation to the Terminal's glyphs as they are at time, then drop the finished ones.
+    /// Apps call this each frame before drawing.
+    pub fn update(&mut self, terminal: &mut Terminal, time: f32) {
+        self.now = time;
+
+        // effects are worked out from scratch every frame, lighting is set separately
+        for target in self.touched.drain(..) {
+            for_each_glyph(terminal, target, &mut |glyph| {
+                glyph.effects = GlyphEffects {
+                    light: glyph.effects.light,
+                    ..GlyphEffects::default()
+                }
+            });
+        }
+
+        for animation in &self.animations {
+            if let (true, Target::Panel(id)) = (animation.show_at_start, animation.target) {
+                if let Some(panel) = terminal.panel_by_id_mut(id) {
+                    panel.show();
+                }
+            }
+
+            let apply: Box<dyn Fn(&mut Glyph)> = match animation.effect {
+                Effect::Offset(tween) => {
+                    let offset = tween.value(time);
+                    Box::new(move |glyph| {
+                        glyph.effects.offset[0] += offset[0];
+                        glyph.effects.offset[1] += offset[1];
+                    })
+                }
+                Effect::FgColor(tween) => {
+                    let color = clamp_color(tween.value(time));
+                    Box::new(move |glyph| glyph.fg_color = color)
+                }
+                Effect::BgColor(tween) => {
+                    let color = clamp_color(tween.value(time));
+                    Box::new(move |glyph| glyph.bg_color = color)
+                }
+                Effect::Opacity(tween) => {
+                    let opacity = tween.value(time).clamp(0.0, 1.0);
+                    Box::new(move |glyph| glyph.effects.opacity *= opacity)
+                }
+                Effect::Tint(color, tween) => {
+                    let amount = tween.value(time).clamp(0.0, 1.0);
+                    Box::new(move |glyph| {
+                        glyph.effects.tint = [color[0], color[1], color[2], amount]
+                    })
+                }
+                Effect::Shake(tween) => {
+                    // the whole target shakes together
+                    let strength = tween.value(time);
+                    let jitter = [
+                        (rand::random::<f32>() * 2.0 - 1.0) * strength,
+                        (rand::random::<f32>() * 2.0 - 1.0) * strength,
+                    ];
+                    Box::new(move |glyph| {
+                        glyph.effects.offset[0] += jitter[0];
+                        glyph.effects.offset[1] += jitter[1];
+                    })
+                }
+            };
+            for_each_glyph(terminal, animation.target, &mut |glyph| apply(glyph));
+            self.touched.push(animation.target);
+        }
+
+        for animation in &self.animations {
+            if let (true, Target::Panel(id)) = (animation.hide_at_end, animation.target) {
+                if animation.effect.finished(time) {
+                    if let Some(panel) = terminal.panel_by_id_mut(id) {
+                        panel.hide();
+                    }
+                }
+            }
+        }
+        self.animations
+            .retain(|animation| !animation.effect.finished(time));
+    }
+}
+
+impl Default for Animator {
+    fn default() -> Animator {
+        Animator::new()
+    }
+}
+
+/// A tint that starts at the color's alpha and fades to nothing.
+fn flash(target: Target, color: [f32; 4], duration: f32) -> Animation {
+    let tween = Tween::new(color[3], 0.0, duration, Easing::OutQuad);
+    Animation::new(target, Effect::Tint([color[0], color[1], color[2]], tween))
+}
+
+fn clamp_color(color: [f32; 4]) -> [f32; 4] {
+    let mut result = color;
+    for c in result.iter_mut() {
+        *c = c.clamp(0.0, 1.0);
+    }
+    result
+}
+
+/// Call f on every Glyph the target covers that is still in the Terminal's Panel tree.
+fn for_each_glyph(terminal: &mut Terminal, target: Target, f: &mut dyn FnMut(&mut Glyph)) {
+    match target {
+        Target::Glyph(id, location) => {
+            if let Some(panel) = terminal.panel_by_id_mut(id) {
+                if panel.rect().contains_point(location) {
+                    f(&mut panel.contents[location.x as usize][location.y as usize]);
+                }
+            }
+        }
+        Target::Panel(id) => {
+            if let Some(panel) = terminal.panel_by_id_mut(id) {
+                for_each_panel_glyph(panel, f);
+            }
+        }
+    }
+}
+
+fn for_each_panel_glyph(panel: &mut Panel, f: &mut dyn FnMut(&mut Glyph)) {
+    for column in &mut panel.contents {
+        for glyph in column {
+            f(glyph);
+        }
+    }
+    for sub_panel in &mut panel.sub_panels {
+        for_each_panel_glyph(sub_panel, f);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::Dimensions;
+
+    const LIGHT: [f32; 3] = [0.5, 0.25, 1.0];
+
+    /// A 3x2 Terminal with a lit Glyph at (1, 1).
+    fn terminal() -> Terminal {
+        let mut terminal = Terminal::new(Dimensions::new(1, 1, 3, 2, 0, 0));
+        terminal.root_panel.contents[1][1].effects.light = LIGHT;
+        terminal
+    }
+
+    fn effects(terminal: &Terminal, x: usize, y: usize) -> GlyphEffects {
+        terminal.root_panel.contents[x][y].effects
+    }
+
+    #[test]
+    fn effects_are_reset_when_animations_end_but_light_stays() {
+        let mut terminal = terminal();
+        let mut animator = Animator::new();
+        let root = terminal.root_panel.id();
+
+        animator.update(&mut terminal, 1.0);
+        animator.slide_glyph(
+            root,
+            Point::new(1, 1),
+            Point::new(0, 1),
+            2.0,
+            Easing::Linear,
+        );
+        animator.flash_panel(root, [1.0, 0.0, 0.0, 0.8], 1.0);
+
+        animator.update(&mut terminal, 2.0);
+        let glyph = effects(&terminal, 1, 1);
+        assert_eq!(glyph.offset, [-0.5, 0.0]);
+        assert_eq!(glyph.tint, [1.0, 0.0, 0.0, 0.0]);
+        assert_eq!(glyph.light, LIGHT);
+        assert_eq!(effects(&terminal, 0, 0).offset, [0.0, 0.0]);
+        assert!(animator.is_animating(Some(root)));
+
+        // the flash is done, the slide is not
+        animator.update(&mut terminal, 2.5);
+        assert_eq!(animator.animations.len(), 1);
+        assert_eq!(effects(&terminal, 0, 0), GlyphEffects::default());
+        assert_eq!(effects(&terminal, 1, 1).tint, [0.0; 4]);
+
+        animator.update(&mut terminal, 3.0);
+        assert!(!animator.is_animating(None));
+        assert_eq!(effects(&terminal, 1, 1).offset, [0.0, 0.0]);
+
+        // the last frame of an animation draws its end state, the next one clears it
+        animator.update(&mut terminal, 3.1);
+        let glyph = effects(&terminal, 1, 1);
+        assert_eq!(
+            glyph,
+            GlyphEffects {
+                light: LIGHT,
+                ..GlyphEffects::default()
+            }
+        );
+    }
+
+    #[test]
+    fn color_changes_stay_after_the_end() {
+        let mut terminal = terminal();
+        let mut animator = Animator::new();
+        let root = terminal.root_panel.id();
+        let red = [1.0, 0.0, 0.0, 1.0];
+
+        let white = [1.0; 4];
+        animator.tween_fg_color(root, Point::new(0, 0), white, red, 1.0, Easing::Linear);
+        animator.update(&mut terminal, 1.0);
+        animator.update(&mut terminal, 2.0);
+        assert!(!animator.is_animating(None));
+        assert_eq!(terminal.root_panel.contents[0][0].fg_color, red);
+    }
+
+    #[test]
+    fn fading_panels_are_shown_and_hidden() {
+        let mut terminal = terminal();
+        let mut animator = Animator::new();
+        let root = terminal.root_panel.id();
+
+        terminal.root_panel.hide();
+        animator.fade_in_panel(root, 1.0, Easing::Linear);
+        animator.update(&mut terminal, 0.5);
+        assert!(!terminal.root_panel.hidden);
+        assert_eq!(effects(&terminal, 2, 0).opacity, 0.5);
+
+        animator.update(&mut terminal, 1.0);
+        animator.fade_out_panel(root, 1.0, Easing::Linear);
+        animator.update(&mut terminal, 1.5);
+        assert!(!terminal.root_panel.hidden);
+        animator.update(&mut terminal, 2.0);
+        assert!(terminal.root_panel.hidden);
+        assert!(!animator.is_animating(Some(root)));
+    }
+
+    #[test]
+    fn cleared_and_out_of_panel_targets_are_harmless() {
+        let mut terminal = terminal();
+        let mut animator = Animator::new();
+        let root = terminal.root_panel.id();
+
+        animator.slide_glyph(
+            root,
+            Point::new(5, 5),
+            Point::new(0, 0),
+            1.0,
+            Easing::Linear,
+        );
+        animator.shake_panel(root, 2.0, 1.0);
+        animator.update(&mut terminal, 0.5);
+        animator.clear();
+        animator.update(&mut terminal, 0.6);
+        for x in 0..3 {
+            for y in 0..2 {
+                assert_eq!(effects(&terminal, x, y).offset, [0.0, 0.0]);
+            }
+        }
+        assert_eq!(effects(&terminal, 1, 1).light, LIGHT);
+    }
+}
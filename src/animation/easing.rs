@@ -0,0 +1,125 @@
+use std::f32::consts::PI;
+
+/// A curve that shapes how a Tween moves from its start to its end value over time.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Easing {
+    Linear,
+    InQuad,
+    OutQuad,
+    InOutQuad,
+    InCubic,
+    OutCubic,
+    InOutCubic,
+    InOutSine,
+    /// Overshoots the end a little and settles back.
+    OutBack,
+    /// Bounces against the end like a dropped ball.
+    OutBounce,
+}
+
+impl Easing {
+    /// How far along the curve is at progress t, both from 0.0 to 1.0. OutBack goes a little past 1.0.
+    pub fn apply(self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Easing::Linear => t,
+            Easing::InQuad => t * t,
+            Easing::OutQuad => 1.0 - (1.0 - t) * (1.0 - t),
+            Easing::InOutQuad => {
+                if t < 0.5 {
+                    2.0 * t * t
+                } else {
+                    1.0 - (-2.0 * t + 2.0).powi(2) / 2.0
+                }
+            }
+            Easing::InCubic => t * t * t,
+            Easing::OutCubic => 1.0 - (1.0 - t).powi(3),
+            Easing::InOutCubic => {
+                if t < 0.5 {
+                    4.0 * t * t * t
+                } else {
+                    1.0 - (-2.0 * t + 2.0).powi(3) / 2.0
+                }
+            }
+            Easing::InOutSine => -((PI * t).cos() - 1.0) / 2.0,
+            Easing::OutBack => {
+                let c1 = 1.701_58;
+                let c3 = c1 + 1.0;
+                1.0 + c3 * (t - 1.0).powi(3) + c1 * (t - 1.0).powi(2)
+            }
+            Easing::OutBounce => {
+                let (n1, d1) = (7.5625, 2.75);
+                if t < 1.0 / d1 {
+                    n1 * t * t
+                } else if t < 2.0 / d1 {
+                    let t = t - 1.5 / d1;
+                    n1 * t * t + 0.75
+                } else if t < 2.5 / d1 {
+                    let t = t - 2.25 / d1;
+                    n1 * t * t + 0.9375
+                } else {
+                    let t = t - 2.625 / d1;
+                    n1 * t * t + 0.984_375
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALL: [Easing; 10] = [
+        Easing::Linear,
+        Easing::InQuad,
+        Easing::OutQuad,
+        Easing::InOutQuad,
+        Easing::InCubic,
+        Easing::OutCubic,
+        Easing::InOutCubic,
+        Easing::InOutSine,
+        Easing::OutBack,
+        Easing::OutBounce,
+    ];
+
+    fn close(a: f32, b: f32) -> bool {
+        (a - b).abs() < 1e-5
+    }
+
+    #[test]
+    fn every_easing_starts_at_0_and_ends_at_1() {
+        for easing in ALL.iter() {
+            assert!(close(easing.apply(0.0), 0.0), "{:?} start", easing);
+            assert!(close(easing.apply(1.0), 1.0), "{:?} end", easing);
+        }
+    }
+
+    #[test]
+    fn progress_is_clamped() {
+        for easing in ALL.iter() {
+            assert_eq!(easing.apply(-1.0), easing.apply(0.0), "{:?}", easing);
+            assert_eq!(easing.apply(2.0), easing.apply(1.0), "{:?}", easing);
+        }
+    }
+
+    #[test]
+    fn in_out_curves_are_halfway_at_the_middle() {
+        for easing in [Easing::InOutQuad, Easing::InOutCubic, Easing::InOutSine].iter() {
+            assert!(close(easing.apply(0.5), 0.5), "{:?}", easing);
+        }
+    }
+
+    #[test]
+    fn only_out_back_overshoots() {
+        let steps = (0..=100).map(|i| i as f32 / 100.0);
+        for easing in ALL.iter() {
+            let highest = steps.clone().map(|t| easing.apply(t)).fold(0.0, f32::max);
+            if *easing == Easing::OutBack {
+                assert!(highest > 1.05);
+            } else {
+                assert!(highest <= 1.0 + 1e-5, "{:?}", easing);
+            }
+        }
+    }
+}
@@ -0,0 +1,9 @@
+mod animator;
+mod easing;
+mod particles;
+mod tween;
+
+pub use animator::*;
+pub use easing::*;
+pub use particles::*;
+pub use tween::*;
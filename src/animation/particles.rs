@@ -0,0 +1,652 @@
+use std::f32::consts::PI;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::animation::Lerp;
+use crate::terminal::{FloatingSprite, FloatingSpriteId, Glyph, PanelId, Terminal};
+
+static NEXT_EMITTER_ID: AtomicU64 = AtomicU64::new(0);
+
+/// A handle to a ParticleEmitter in a ParticleSystem.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct EmitterId(u64);
+
+impl EmitterId {
+    /// An EmitterId no other ParticleEmitter has.
+    pub fn next() -> EmitterId {
+        EmitterId(NEXT_EMITTER_ID.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+/// Where the particles of a ParticleEmitter are drawn.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ParticleTarget {
+    /// As FloatingSprites on the Terminal, moving smoothly.
+    Floating,
+    /// As glyphs snapped to the cells of a Panel. The emitter owns the Panel's contents and clears them every frame.
+    Panel(PanelId),
+}
+
+/// One short-lived particle. Positions are in terminal cells, velocities in cells per second.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Particle {
+    pub position: [f32; 2],
+    pub velocity: [f32; 2],
+    /// Seconds since the particle was spawned.
+    pub age: f32,
+    /// Seconds the particle lives for.
+    pub lifetime: f32,
+
+    sprite: Option<FloatingSpriteId>,
+}
+
+impl Particle {
+    /// How far through its life the particle is, from 0.0 to 1.0.
+    pub fn progress(&self) -> f32 {
+        if self.lifetime <= 0.0 {
+            return 1.0;
+        }
+        (self.age / self.lifetime).clamp(0.0, 1.0)
+    }
+}
+
+/// Spawns particles at a position and moves them each frame, for sparks, blood, smoke and spell effects.
+/// Ranges are [min, max] and each particle gets a random value between them.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParticleEmitter {
+    /// Center of the area particles spawn in, in terminal cells.
+    pub position: [f32; 2],
+    /// Width and height of the area particles spawn in.
+    pub area: [f32; 2],
+    /// Particles spawned per second while emitting.
+    pub rate: f32,
+    /// Whether particles are spawned at the rate, bursts are spawned either way.
+    pub emitting: bool,
+    pub lifetime: [f32; 2],
+    /// Cells per second.
+    pub speed: [f32; 2],
+    /// The direction particles move in, counter-clockwise from the right in radians.
+    pub direction: f32,
+    /// How far particles may move away from the direction on either side, PI for all directions.
+    pub spread: f32,
+    /// Added to the velocity of every particle each second, e.g. [0.0, -10.0] to make them fall.
+    pub gravity: [f32; 2],
+    /// The share of its velocity a particle loses each second, from 0.0 to 1.0.
+    pub drag: f32,
+    /// Colors particles go through over their life, evenly spaced.
+    pub colors: Vec<[f32; 4]>,
+    /// Sprites particles go through over their life, evenly spaced.
+    pub sprites: Vec<String>,
+    /// Size of floating particles at the start and the end of their life, in cells.
+    pub size: [f32; 2],
+    pub target: ParticleTarget,
+    /// The layer floating particles are drawn on.
+    pub layer: usize,
+    pub particles: Vec<Particle>,
+
+    to_spawn: f32,
+}
+
+impl ParticleEmitter {
+    /// Create a new emitter drawing floating particles at position. It isn't emitting, see ParticleEmitter::started or ParticleEmitter::burst.
+    /// Particles live a second, move one to two cells per second in any direction and fade from white.
+    pub fn new(position: [f32; 2], sprite_id: &str) -> ParticleEmitter {
+        ParticleEmitter {
+            position,
+            area: [0.0, 0.0],
+            rate: 10.0,
+            emitting: false,
+            lifetime: [1.0, 1.0],
+            speed: [1.0, 2.0],
+            direction: 0.0,
+            spread: PI,
+            gravity: [0.0, 0.0],
+            drag: 0.0,
+            colors: vec![[1.0, 1.0, 1.0, 1.0], [1.0, 1.0, 1.0, 0.0]],
+            sprites: vec![sprite_id.to_string()],
+            size: [1.0, 1.0],
+            target: ParticleTarget::Floating,
+            layer: 0,
+            particles: Vec::new(),
+            to_spawn: 0.0,
+        }
+    }
+
+    /// A short burst-ready spray of bright sparks that fall and cool from yellow to red.
+    pub fn sparks(position: [f32; 2]) -> ParticleEmitter {
+        ParticleEmitter::new(position, "*")
+            .with_lifetime([0.3, 0.7])
+            .with_speed([4.0, 9.0])
+            .with_gravity([0.0, -12.0])
+            .with_colors(vec![
+                [1.0, 1.0, 0.6, 1.0],
+                [1.0, 0.6, 0.1, 1.0],
+                [0.8, 0.1, 0.0, 0.0],
+            ])
+            .with_size([0.5, 0.2])
+    }
+
+    /// Drops of blood thrown in a direction that fall and darken.
+    pub fn blood(position: [f32; 2], direction: f32) -> ParticleEmitter {
+        ParticleEmitter::new(position, ".")
+            .with_lifetime([0.4, 0.8])
+            .with_speed([3.0, 7.0])
+            .with_direction(direction, PI / 4.0)
+            .with_gravity([0.0, -15.0])
+            .with_colors(vec![[0.8, 0.0, 0.0, 1.0], [0.4, 0.0, 0.0, 0.0]])
+    }
+
+    /// Slow grey puffs that rise, grow and fade. Starts emitting.
+    pub fn smoke(position: [f32; 2]) -> ParticleEmitter {
+        ParticleEmitter::new(position, "░")
+            .with_rate(6.0)
+            .with_lifetime([1.5, 2.5])
+            .with_speed([0.5, 1.2])
+            .with_direction(PI / 2.0, PI / 6.0)
+            .with_drag(0.3)
+            .with_colors(vec![[0.5, 0.5, 0.5, 0.8], [0.3, 0.3, 0.3, 0.0]])
+            .with_size([0.8, 2.0])
+            .started()
+    }
+
+    /// Twinkling motes drifting out of an area, going through a few star sprites. Starts emitting.
+    pub fn magic(position: [f32; 2], color: [f32; 4]) -> ParticleEmitter {
+        let mut faded = color;
+        faded[3] = 0.0;
+        ParticleEmitter::new(position, "+")
+            .with_area([1.0, 1.0])
+            .with_rate(15.0)
+            .with_lifetime([0.6, 1.2])
+            .with_speed([0.3, 1.0])
+            .with_sprites(&["+", "*", "·"])
+            .with_colors(vec![[1.0, 1.0, 1.0, 1.0], color, faded])
+            .with_size([0.6, 0.3])
+            .started()
+    }
+
+    /// Returns a copy of this ParticleEmitter spawning in an area of the given size around its position.
+    pub fn with_area(self, area: [f32; 2]) -> ParticleEmitter {
+        let mut new = self;
+        new.area = area;
+        new
+    }
+
+    /// Returns a copy of this ParticleEmitter with the given spawn rate.
+    pub fn with_rate(self, rate: f32) -> ParticleEmitter {
+        let mut new = self;
+        new.rate = rate;
+        new
+    }
+
+    /// Returns a copy of this ParticleEmitter with the given lifetime range.
+    pub fn with_lifetime(self, lifetime: [f32; 2]) -> ParticleEmitter {
+        let mut new = self;
+        new.lifetime = lifetime;
+        new
+    }
+
+    /// Returns a copy of this ParticleEmitter with the given speed range.
+    pub fn with_speed(self, speed: [f32; 2]) -> ParticleEmitter {
+        let mut new = self;
+        new.speed = speed;
+        new
+    }
+
+    /// Returns a copy of this ParticleEmitter sending particles in a direction, up to spread away from it.
+    pub fn with_direction(self, direction: f32, spread: f32) -> ParticleEmitter {
+        let mut new = self;
+        new.direction = direction;
+        new.spread = spread;
+        new
+    }
+
+    /// Returns a copy of this ParticleEmitter with the given gravity.
+    pub fn with_gravity(self, gravity: [f32; 2]) -> ParticleEmitter {
+        let mut new = self;
+        new.gravity = gravity;
+        new
+    }
+
+    /// Returns a copy of this ParticleEmitter with the given drag.
+    pub fn with_drag(self, drag: f32) -> ParticleEmitter {
+        let mut new = self;
+        new.drag = drag;
+        new
+    }
+
+    /// Returns a copy of this ParticleEmitter with the given color gradient.
+    pub fn with_colors(self, colors: Vec<[f32; 4]>) -> ParticleEmitter {
+        let mut new = self;
+        new.colors = colors;
+        new
+    }
+
+    /// Returns a copy of this ParticleEmitter with the given sprite sequence.
+    pub fn with_sprites(self, sprite_ids: &[&str]) -> ParticleEmitter {
+        let mut new = self;
+        new.sprites = sprite_ids.iter().map(|id| id.to_string()).collect();
+        new
+    }
+
+    /// Returns a copy of this ParticleEmitter with the given start and end size.
+    pub fn with_size(self, size: [f32; 2]) -> ParticleEmitter {
+        let mut new = self;
+        new.size = size;
+        new
+    }
+
+    /// Returns a copy of this ParticleEmitter drawing into the given target.
+    pub fn with_target(self, target: ParticleTarget) -> ParticleEmitter {
+        let mut new = self;
+        new.target = target;
+        new
+    }
+
+    /// Returns a copy of this ParticleEmitter drawing floating particles on the given layer.
+    pub fn with_layer(self, layer: usize) -> ParticleEmitter {
+        let mut new = self;
+        new.layer = layer;
+        new
+    }
+
+    /// Returns a copy of this ParticleEmitter that is emitting.
+    pub fn started(self) -> ParticleEmitter {
+        let mut new = self;
+        new.emitting = true;
+        new
+    }
+
+    /// Spawn count particles at once.
+    pub fn burst(&mut self, count: usize) {
+        for _ in 0..count {
+            self.spawn();
+        }
+    }
+
+    /// The color of a particle a fraction t through its life.
+    pub fn color_at(&self, t: f32) -> [f32; 4] {
+        match self.colors.len() {
+            0 => [1.0, 1.0, 1.0, 1.0],
+            1 => self.colors[0],
+            len => {
+                let scaled = t.clamp(0.0, 1.0) * (len - 1) as f32;
+                let index = (scaled as usize).min(len - 2);
+                self.colors[index].lerp(self.colors[index + 1], scaled - index as f32)
+            }
+        }
+    }
+
+    /// The sprite of a particle a fraction t through its life.
+    pub fn sprite_at(&self, t: f32) -> &str {
+        if self.sprites.is_empty() {
+            return "empty";
+        }
+        let index = (t.clamp(0.0, 1.0) * self.sprites.len() as f32) as usize;
+        &self.sprites[index.min(self.sprites.len() - 1)]
+    }
+
+    fn spawn(&mut self) {
+        let angle = self.direction + (rand::random::<f32>() * 2.0 - 1.0) * self.spread;
+        let speed = random_in(self.speed);
+        let particle = Particle {
+            position: [
+                self.position[0] + (rand::random::<f32>() - 0.5) * self.area[0],
+                self.position[1] + (rand::random::<f32>() - 0.5) * self.area[1],
+            ],
+            velocity: [angle.cos() * speed, angle.sin() * speed],
+            age: 0.0,
+            lifetime: random_in(self.lifetime),
+            sprite: None,
+        };
+        self.particles.push(particle);
+    }
+
+    /// Spawn, move and age particles by delta seconds, then draw them to the Terminal.
+    pub fn update(&mut self, terminal: &mut Terminal, delta: f32) {
+        if self.emitting {
+            self.to_spawn += self.rate * delta;
+            while self.to_spawn >= 1.0 {
+                self.spawn();
+                self.to_spawn -= 1.0;
+            }
+        }
+
+        let damping = (1.0 - self.drag).clamp(0.0, 1.0).powf(delta);
+        for particle in &mut self.particles {
+            particle.age += delta;
+            for axis in 0..2 {
+                particle.velocity[axis] =
+                    (particle.velocity[axis] + self.gravity[axis] * delta) * damping;
+                particle.position[axis] += particle.velocity[axis] * delta;
+            }
+        }
+
+        let (alive, dead): (Vec<Particle>, Vec<Particle>) = self
+            .particles
+            .drain(..)
+            .partition(|particle| particle.age < particle.lifetime);
+        self.particles = alive;
+        for sprite in dead.iter().filter_map(|particle| particle.sprite) {
+            terminal.remove_floating_sprite(sprite);
+        }
+
+        self.draw(terminal);
+    }
+
+    /// Draw the particles to the Terminal as they are now.
+    fn draw(&mut self, terminal: &mut Terminal) {
+        match self.target {
+            ParticleTarget::Floating => {
+                for index in 0..self.particles.len() {
+                    let particle = &self.particles[index];
+                    let t = particle.progress();
+                    let size = self.size[0].lerp(self.size[1], t);
+                    let (sprite_id, tint) = (self.sprite_at(t), self.color_at(t));
+
+                    match particle
+                        .sprite
+                        .and_then(|id| terminal.floating_sprite_mut(id))
+                    {
+                        Some(drawn) => {
+                            if drawn.sprite_id != sprite_id {
+                                drawn.sprite_id = sprite_id.to_string();
+                            }
+                            drawn.position = particle.position;
+                            drawn.tint = tint;
+                            drawn.scale = [size, size];
+                            drawn.layer = self.layer;
+                        }
+                        None => {
+                            let sprite = FloatingSprite::new(sprite_id, particle.position)
+                                .with_tint(tint)
+                                .with_scale([size, size])
+                                .with_layer(self.layer);
+                            self.particles[index].sprite =
+                                Some(terminal.add_floating_sprite(sprite));
+                        }
+                    }
+                }
+            }
+            ParticleTarget::Panel(id) => {
+                let panel = match terminal.panel_by_id_mut(id) {
+                    Some(panel) => panel,
+                    None => return,
+                };
+                for column in &mut panel.contents {
+                    for glyph in column.iter_mut() {
+                        *glyph = Glyph::empty(glyph.location);
+                    }
+                }
+
+                let offset = panel.dims.offset;
+                for particle in &self.particles {
+                    let x = particle.position[0] - offset.x as f32;
+                    let y = particle.position[1] - offset.y as f32;
+                    if x < 0.0 || y < 0.0 {
+                        continue;
+                    }
+                    let (x, y) = (x as usize, y as usize);
+                    if let Some(glyph) = panel
+                        .contents
+                        .get_mut(x)
+                        .and_then(|column| column.get_mut(y))
+                    {
+                        let t = particle.progress();
+                        glyph.sprite_id = self.sprite_at(t).to_string();
+                        glyph.fg_color = self.color_at(t);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Take every particle off the Terminal and forget them.
+    pub fn clear(&mut self, terminal: &mut Terminal) {
+        for sprite in self
+            .particles
+            .drain(..)
+            .filter_map(|particle| particle.sprite)
+        {
+            terminal.remove_floating_sprite(sprite);
+        }
+        // clears a Panel target
+        self.draw(terminal);
+    }
+}
+
+/// Runs ParticleEmitters, driven by the App clock.
+pub struct ParticleSystem {
+    emitters: Vec<(EmitterId, ParticleEmitter)>,
+    stopped: Vec<EmitterId>,
+    last_time: Option<f32>,
+}
+
+impl ParticleSystem {
+    /// Create a new ParticleSystem with no emitters.
+    pub fn new() -> ParticleSystem {
+        ParticleSystem {
+            emitters: Vec::new(),
+            stopped: Vec::new(),
+            last_time: None,
+        }
+    }
+
+    /// Add a ParticleEmitter, returns its id.
+    pub fn add(&mut self, emitter: ParticleEmitter) -> EmitterId {
+        let id = EmitterId::next();
+        self.emitters.push((id, emitter));
+        id
+    }
+
+    /// Spawn count particles from an emitter once, then remove it when they are gone. For hits, explosions and the like.
+    pub fn burst(&mut self, emitter: ParticleEmitter, count: usize) -> EmitterId {
+        let mut emitter = emitter;
+        emitter.burst(count);
+        let id = self.add(emitter);
+        self.stop(id);
+        id
+    }
+
+    /// Get the ParticleEmitter with the given id.
+    pub fn emitter(&self, id: EmitterId) -> Option<&ParticleEmitter> {
+        self.emitters
+            .iter()
+            .find(|(emitter_id, _)| *emitter_id == id)
+            .map(|(_, emitter)| emitter)
+    }
+
+    /// Get the ParticleEmitter with the given id mutably, e.g. to move it along with what it is attached to.
+    pub fn emitter_mut(&mut self, id: EmitterId) -> Option<&mut ParticleEmitter> {
+        self.emitters
+            .iter_mut()
+            .find(|(emitter_id, _)| *emitter_id == id)
+            .map(|(_, emitter)| emitter)
+    }
+
+    /// Stop an emitter from spawning particles and remove it once the ones it spawned are gone.
+    pub fn stop(&mut self, id: EmitterId) {
+        if let Some(emitter) = self.emitter_mut(id) {
+            emitter.emitting = false;
+            self.stopped.push(id);
+        }
+    }
+
+    /// The number of live particles of all emitters.
+    pub fn particle_count(&self) -> usize {
+        self.emitters
+            .iter()
+            .map(|(_, emitter)| emitter.particles.len())
+            .sum()
+    }
+
+    /// Take every particle off the Terminal and remove every emitter.
+    pub fn clear(&mut self, terminal: &mut Terminal) {
+        for (_, emitter) in &mut self.emitters {
+            emitter.clear(terminal);
+        }
+        self.emitters.clear();
+        self.stopped.clear();
+    }
+
+    /// Update every emitter for the time passed since the last update, then remove stopped emitters without particles.
+    /// Apps call this each frame before drawing.
+    pub fn update(&mut self, terminal: &mut Terminal, time: f32) {
+        let delta = self.last_time.map_or(0.0, |last| (time - last).max(0.0));
+        self.last_time = Some(time);
+
+        for (_, emitter) in &mut self.emitters {
+            emitter.update(terminal, delta);
+        }
+
+        let stopped = &mut self.stopped;
+        self.emitters.retain(|(id, emitter)| {
+            let done = emitter.particles.is_empty() && stopped.contains(id);
+            if done {
+                stopped.retain(|stopped_id| stopped_id != id);
+            }
+            !done
+        });
+    }
+}
+
+impl Default for ParticleSystem {
+    fn default() -> ParticleSystem {
+        ParticleSystem::new()
+    }
+}
+
+/// A random value between the two ends of a range.
+fn random_in(range: [f32; 2]) -> f32 {
+    range[0] + (range[1] - range[0]) * rand::random::<f32>()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::{Dimensions, Point};
+
+    fn terminal() -> Terminal {
+        Terminal::new(Dimensions::new(1, 1, 4, 3, 0, 0))
+    }
+
+    /// An emitter whose particles stay where they spawn for a second.
+    fn still(sprite_id: &str) -> ParticleEmitter {
+        ParticleEmitter::new([1.5, 1.5], sprite_id).with_speed([0.0, 0.0])
+    }
+
+    #[test]
+    fn expired_particles_take_their_sprites_with_them() {
+        let mut terminal = terminal();
+        let mut emitter = still("*");
+        emitter.burst(3);
+        emitter.particles[0].lifetime = 2.0;
+
+        emitter.update(&mut terminal, 0.5);
+        assert_eq!(emitter.particles.len(), 3);
+        assert_eq!(terminal.floating_sprites.len(), 3);
+        assert_eq!(terminal.floating_sprites[0].position, [1.5, 1.5]);
+
+        emitter.update(&mut terminal, 0.5);
+        assert_eq!(emitter.particles.len(), 1);
+        assert_eq!(terminal.floating_sprites.len(), 1);
+        let sprite = emitter.particles[0].sprite.unwrap();
+        assert!(terminal.floating_sprite(sprite).is_some());
+
+        emitter.update(&mut terminal, 1.0);
+        assert!(emitter.particles.is_empty());
+        assert!(terminal.floating_sprites.is_empty());
+    }
+
+    #[test]
+    fn sprites_the_game_removed_are_added_again() {
+        let mut terminal = terminal();
+        let mut emitter = still("*");
+        emitter.burst(1);
+        emitter.update(&mut terminal, 0.1);
+        terminal.floating_sprites.clear();
+
+        emitter.update(&mut terminal, 0.1);
+        assert_eq!(terminal.floating_sprites.len(), 1);
+        emitter.update(&mut terminal, 1.0);
+        assert!(terminal.floating_sprites.is_empty());
+    }
+
+    #[test]
+    fn particles_fall_and_slow_down() {
+        let mut terminal = terminal();
+        let mut emitter = still("*").with_gravity([0.0, -2.0]);
+        emitter.burst(1);
+        emitter.update(&mut terminal, 0.5);
+        assert_eq!(emitter.particles[0].velocity, [0.0, -1.0]);
+        assert_eq!(emitter.particles[0].position, [1.5, 1.0]);
+
+        let mut emitter = ParticleEmitter::new([0.0, 0.0], "*")
+            .with_speed([4.0, 4.0])
+            .with_direction(0.0, 0.0)
+            .with_drag(0.75);
+        emitter.burst(1);
+        emitter.update(&mut terminal, 0.5);
+        assert_eq!(emitter.particles[0].velocity, [2.0, 0.0]);
+    }
+
+    #[test]
+    fn emitting_spawns_at_the_rate() {
+        let mut terminal = terminal();
+        let mut emitter = still("*").with_rate(10.0).started();
+        emitter.update(&mut terminal, 0.25);
+        assert_eq!(emitter.particles.len(), 2);
+        // the half particle left over is spawned next time
+        emitter.update(&mut terminal, 0.25);
+        assert_eq!(emitter.particles.len(), 5);
+
+        emitter.emitting = false;
+        emitter.update(&mut terminal, 0.25);
+        assert_eq!(emitter.particles.len(), 5);
+    }
+
+    #[test]
+    fn panel_particles_are_snapped_to_cells_and_cleared() {
+        let mut terminal = terminal();
+        let root = terminal.root_panel.id();
+        let mut emitter = still("*").with_target(ParticleTarget::Panel(root));
+        emitter.burst(1);
+
+        emitter.update(&mut terminal, 0.0);
+        assert!(terminal.floating_sprites.is_empty());
+        let glyph = terminal.root_panel.get(Point::new(1, 1)).unwrap();
+        assert_eq!(glyph.sprite_id, "*");
+        assert_eq!(glyph.fg_color, [1.0, 1.0, 1.0, 1.0]);
+
+        emitter.update(&mut terminal, 1.0);
+        let glyph = terminal.root_panel.get(Point::new(1, 1)).unwrap();
+        assert_eq!(glyph.sprite_id, "empty");
+    }
+
+    #[test]
+    fn colors_and_sprites_follow_the_life_of_a_particle() {
+        let emitter = still("a")
+            .with_sprites(&["a", "b"])
+            .with_colors(vec![[0.0; 4], [1.0; 4], [0.0; 4]]);
+        assert_eq!(emitter.sprite_at(0.0), "a");
+        assert_eq!(emitter.sprite_at(0.5), "b");
+        assert_eq!(emitter.sprite_at(1.0), "b");
+        assert_eq!(emitter.color_at(0.25), [0.5; 4]);
+        assert_eq!(emitter.color_at(0.5), [1.0; 4]);
+        assert_eq!(emitter.color_at(1.0), [0.0; 4]);
+    }
+
+    #[test]
+    fn bursts_are_removed_when_their_particles_are_gone() {
+        let mut terminal = terminal();
+        let mut system = ParticleSystem::new();
+        let id = system.burst(still("*"), 4);
+        let running = system.add(still("*"));
+
+        system.update(&mut terminal, 0.0);
+        assert_eq!(system.particle_count(), 4);
+        system.update(&mut terminal, 1.0);
+        assert_eq!(system.particle_count(), 0);
+        assert!(system.emitter(id).is_none());
+        assert!(system.emitter(running).is_some());
+        assert!(terminal.floating_sprites.is_empty());
+    }
+}
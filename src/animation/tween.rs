@@ -0,0 +1,114 @@
+use crate::animation::Easing;
+
+/// A value that can be interpolated between two others.
+pub trait Lerp: Copy {
+    /// The value a fraction t of the way from self to other.
+    fn lerp(self, other: Self, t: f32) -> Self;
+}
+
+impl Lerp for f32 {
+    fn lerp(self, other: f32, t: f32) -> f32 {
+        self + (other - self) * t
+    }
+}
+
+impl Lerp for [f32; 2] {
+    fn lerp(self, other: [f32; 2], t: f32) -> [f32; 2] {
+        [self[0].lerp(other[0], t), self[1].lerp(other[1], t)]
+    }
+}
+
+impl Lerp for [f32; 4] {
+    fn lerp(self, other: [f32; 4], t: f32) -> [f32; 4] {
+        [
+            self[0].lerp(other[0], t),
+            self[1].lerp(other[1], t),
+            self[2].lerp(other[2], t),
+            self[3].lerp(other[3], t),
+        ]
+    }
+}
+
+/// A change from one value to another over duration seconds starting at a time, shaped by an Easing.
+/// Times are seconds on the App clock.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Tween<T: Lerp> {
+    pub from: T,
+    pub to: T,
+    pub start: f32,
+    pub duration: f32,
+    pub easing: Easing,
+}
+
+impl<T: Lerp> Tween<T> {
+    /// Create a new Tween starting at time 0.0. An Animator sets the start when it is added.
+    pub fn new(from: T, to: T, duration: f32, easing: Easing) -> Tween<T> {
+        Tween {
+            from,
+            to,
+            start: 0.0,
+            duration,
+            easing,
+        }
+    }
+
+    /// How far through its duration the Tween is at time, from 0.0 to 1.0.
+    pub fn progress(&self, time: f32) -> f32 {
+        if self.duration <= 0.0 {
+            return 1.0;
+        }
+        ((time - self.start) / self.duration).clamp(0.0, 1.0)
+    }
+
+    /// The eased value at time.
+    pub fn value(&self, time: f32) -> T {
+        let t = self.easing.apply(self.progress(time));
+        self.from.lerp(self.to, t)
+    }
+
+    /// Check if the Tween has reached its end at time.
+    pub fn finished(&self, time: f32) -> bool {
+        time >= self.start + self.duration
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tweens_run_from_start_to_start_plus_duration() {
+        let mut tween = Tween::new(10.0, 20.0, 2.0, Easing::Linear);
+        tween.start = 1.0;
+
+        assert_eq!(tween.value(0.0), 10.0);
+        assert!(!tween.finished(0.0));
+        assert_eq!(tween.value(1.0), 10.0);
+        assert_eq!(tween.progress(2.0), 0.5);
+        assert_eq!(tween.value(2.0), 15.0);
+        assert!(!tween.finished(2.9));
+        assert!(tween.finished(3.0));
+        assert_eq!(tween.value(3.0), 20.0);
+        // the end value holds after it finishes
+        assert_eq!(tween.value(10.0), 20.0);
+    }
+
+    #[test]
+    fn zero_duration_is_finished_at_once() {
+        let tween = Tween::new([0.0, 0.0], [1.0, -1.0], 0.0, Easing::OutBounce);
+        assert_eq!(tween.progress(0.0), 1.0);
+        assert_eq!(tween.value(0.0), [1.0, -1.0]);
+        assert!(tween.finished(0.0));
+    }
+
+    #[test]
+    fn colors_are_lerped_per_channel() {
+        let tween = Tween::new(
+            [0.0, 1.0, 0.5, 1.0],
+            [1.0, 0.0, 0.5, 0.0],
+            4.0,
+            Easing::Linear,
+        );
+        assert_eq!(tween.value(1.0), [0.25, 0.75, 0.5, 0.75]);
+    }
+}
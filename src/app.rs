@@ -1,14 +1,25 @@
-use std::path::Path;
+use std::env;
+use std::path::{Path, PathBuf};
 
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 use glium;
 use glium::glutin;
 use glium::Surface;
 
+use crate::animation::{Animator, ParticleSystem};
 use crate::error::OozeResult;
-use crate::geometry::Dimensions;
-use crate::graphics::{get_shader, SpriteMap};
+use crate::frame::{Frame, GameCallback};
+use crate::geometry::{Dimensions, Point};
+use image::RgbaImage;
+
+use crate::graphics::{
+    capture_front_buffer, get_shader, render_terminal, save_png, CaptureSource, PostProcessChain,
+    PostProcessPass, RecordFormat, Recorder, Scaling, SpriteMap, UniformData, UniformSet,
+    DEFAULT_FRAGMENT_SHADER, DEFAULT_VERTEX_SHADER,
+};
+use crate::hotreload::FileWatcher;
+use crate::input::{from_glutin, InputEvent};
 use crate::terminal::Terminal;
 
 /// This should be implemented by the user's main Game or GameState struct.
@@ -22,24 +33,62 @@ pub struct App<G: GameState> {
     pub events_loop: glutin::EventsLoop,
     pub display: glium::Display,
     pub program: glium::Program,
+    pub glyph_shader_paths: Option<(PathBuf, PathBuf)>,
+    pub glyph_uniforms: UniformSet,
+    pub post_process: PostProcessChain,
 
     pub terminal: Terminal,
+    /// How many window pixels each pixel of a glyph is drawn as.
+    pub scale: f32,
+    pub animator: Animator,
+    pub particles: ParticleSystem,
 
     pub sprites: SpriteMap,
 
+    pub resource_root: PathBuf,
+
+    pub title: String,
+    pub hot_reload: Option<FileWatcher>,
+    pub reload_error: Option<String>,
+    /// The built-in font the reload error overlay is drawn with, loaded the first time it is shown.
+    pub overlay_font: Option<SpriteMap>,
+    pub recorder: Option<Recorder>,
+
+    /// The input events polled this frame.
+    pub events: Vec<InputEvent>,
+
     pub closed: bool,
+    pub start_time: Instant,
 
-    pub update_game_callback: fn(&mut App<G>, &mut G),
-    pub handle_events_callback: fn(&mut App<G>, &mut G),
+    pub update_game_callback: GameCallback<G>,
+    pub handle_events_callback: GameCallback<G>,
 }
 
 impl<G: GameState> App<G> {
     /// Create a new App.
+    /// A relative sprite_sheet_path is resolved against the default resource root, see App::resource_path.
     pub fn new(
         dims: Dimensions,
         scale: f32,
         title: &str,
         sprite_sheet_path: &Path,
+    ) -> OozeResult<App<G>> {
+        App::with_resource_root(
+            dims,
+            scale,
+            title,
+            &default_resource_root(),
+            sprite_sheet_path,
+        )
+    }
+
+    /// Create a new App whose relative resource paths are resolved against resource_root, starting with sprite_sheet_path.
+    pub fn with_resource_root(
+        dims: Dimensions,
+        scale: f32,
+        title: &str,
+        resource_root: &Path,
+        sprite_sheet_path: &Path,
     ) -> OozeResult<App<G>> {
         let (events_loop, display) = init_window(
             (dims.glyph_size.x as f32 * dims.term_size.x as f32 * scale) as usize,
@@ -51,27 +100,37 @@ impl<G: GameState> App<G> {
 
         let program = glium::Program::from_source(
             &display,
-            get_shader(Path::new(
-                r#"resources\shaders\vertex\v_shader_default.vert"#,
-            ))?
-            .as_str(),
-            get_shader(Path::new(
-                r#"resources\shaders\fragment\f_shader_default.frag"#,
-            ))?
-            .as_str(),
+            DEFAULT_VERTEX_SHADER,
+            DEFAULT_FRAGMENT_SHADER,
             None,
         )
         .expect("Failed while creating shader program");
 
-        let sprites = SpriteMap::from_sheet(&display, sprite_sheet_path)?;
+        let resource_root = resource_root.to_path_buf();
+
+        let sprites = SpriteMap::from_sheet(&display, &resource_root.join(sprite_sheet_path))?;
 
         let app = App {
             events_loop,
             display,
             program,
+            glyph_shader_paths: None,
+            glyph_uniforms: UniformSet::new(),
+            post_process: PostProcessChain::new(),
             terminal,
+            scale,
+            animator: Animator::new(),
+            particles: ParticleSystem::new(),
             sprites,
+            resource_root,
+            title: title.to_string(),
+            hot_reload: None,
+            reload_error: None,
+            overlay_font: None,
+            recorder: None,
+            events: Vec::new(),
             closed: false,
+            start_time: Instant::now(),
             update_game_callback: default_update_callback,
             handle_events_callback: default_handle_events_callback,
         };
@@ -79,23 +138,398 @@ impl<G: GameState> App<G> {
         Ok(app)
     }
 
+    /// Set the folder that relative resource paths are resolved against.
+    pub fn set_resource_root<P: AsRef<Path>>(&mut self, root: P) {
+        self.resource_root = root.as_ref().to_path_buf();
+    }
+
+    /// Resolve a path against this App's resource root. Absolute paths are returned unchanged.
+    pub fn resource_path<P: AsRef<Path>>(&self, path: P) -> PathBuf {
+        self.resource_root.join(path)
+    }
+
+    /// Load another spritesheet into this App's SpriteMap, optionally under a namespace.
+    /// Cells of a different size than the terminal's glyph size are fitted with the given Scaling rule.
+    pub fn add_sheet(
+        &mut self,
+        sheet_path: &Path,
+        namespace: Option<&str>,
+        scaling: Scaling,
+    ) -> OozeResult<()> {
+        self.sprites.add_sheet(
+            &self.display,
+            &self.resource_path(sheet_path),
+            namespace,
+            self.terminal.dims.glyph_size,
+            scaling,
+        )
+    }
+
+    /// Load a folder of individual png images into this App's SpriteMap, optionally under a namespace.
+    /// Images of a different size than the terminal's glyph size are fitted with the given Scaling rule.
+    pub fn add_files(
+        &mut self,
+        resource_folder: &Path,
+        namespace: Option<&str>,
+        scaling: Scaling,
+    ) -> OozeResult<()> {
+        self.sprites.add_files(
+            &self.display,
+            &self.resource_path(resource_folder),
+            namespace,
+            self.terminal.dims.glyph_size,
+            scaling,
+        )
+    }
+
+    /// Load the built-in CP437 font sheet into this App's SpriteMap, optionally under a namespace.
+    /// Each sprite id is the character it shows, so text can be placed one character per Glyph.
+    #[cfg(feature = "default-font")]
+    pub fn add_default_font(
+        &mut self,
+        namespace: Option<&str>,
+        scaling: Scaling,
+    ) -> OozeResult<()> {
+        self.sprites.add_default_font(
+            &self.display,
+            namespace,
+            self.terminal.dims.glyph_size,
+            scaling,
+        )
+    }
+
+    /// Replace the shader program used to draw each glyph.
+    /// Besides its own attributes and uniforms it receives `bg_color`, `fg_color`, `tex`, `time`, `screen_size` and everything in App.glyph_uniforms.
+    pub fn set_glyph_shader(
+        &mut self,
+        vertex_shader: &str,
+        fragment_shader: &str,
+    ) -> OozeResult<()> {
+        self.program =
+            glium::Program::from_source(&self.display, vertex_shader, fragment_shader, None)?;
+        self.glyph_shader_paths = None;
+
+        Ok(())
+    }
+
+    /// Replace the shader program used to draw each glyph with one loaded from files, see App::set_glyph_shader.
+    /// Relative paths are resolved against the resource root. The files are watched when hot reloading is enabled.
+    pub fn set_glyph_shader_files(
+        &mut self,
+        vertex_path: &Path,
+        fragment_path: &Path,
+    ) -> OozeResult<()> {
+        let vertex_path = self.resource_path(vertex_path);
+        let fragment_path = self.resource_path(fragment_path);
+
+        self.set_glyph_shader(&get_shader(&vertex_path)?, &get_shader(&fragment_path)?)?;
+        self.glyph_shader_paths = Some((vertex_path, fragment_path));
+
+        Ok(())
+    }
+
+    /// Add a full-screen pass to the end of this App's post-processing chain.
+    pub fn add_post_process(&mut self, pass: PostProcessPass) {
+        self.post_process.push(pass);
+    }
+
+    /// Turn on hot reloading, a development mode where the loaded sprite sheets, their metadata and shader files are watched
+    /// and reloaded in place when they change. Errors while reloading are shown on screen instead of stopping the App.
+    pub fn enable_hot_reload(&mut self) {
+        let mut watcher = FileWatcher::new(Duration::from_millis(500));
+        for path in self.watched_paths() {
+            watcher.watch(path);
+        }
+        self.hot_reload = Some(watcher);
+    }
+
+    /// Turn off hot reloading.
+    pub fn disable_hot_reload(&mut self) {
+        self.hot_reload = None;
+    }
+
+    /// Reload every sprite sheet, sprite folder and shader file this App was set up with.
+    /// Nothing is replaced if any of them fails to load.
+    pub fn reload(&mut self) -> OozeResult<()> {
+        let program = match &self.glyph_shader_paths {
+            Some((vertex_path, fragment_path)) => Some(glium::Program::from_source(
+                &self.display,
+                &get_shader(vertex_path)?,
+                &get_shader(fragment_path)?,
+                None,
+            )?),
+            None => None,
+        };
+
+        let mut pass_programs = Vec::with_capacity(self.post_process.passes.len());
+        for pass in &self.post_process.passes {
+            pass_programs.push(pass.reload_program(&self.display)?);
+        }
+
+        self.sprites.reload(&self.display)?;
+
+        if let Some(program) = program {
+            self.program = program;
+        }
+        for (pass, program) in self.post_process.passes.iter_mut().zip(pass_programs) {
+            if let Some(program) = program {
+                pass.program = program;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// All the files hot reloading watches.
+    fn watched_paths(&self) -> Vec<PathBuf> {
+        let mut result = self.sprites.source_paths();
+        if let Some((vertex_path, fragment_path)) = &self.glyph_shader_paths {
+            result.push(vertex_path.clone());
+            result.push(fragment_path.clone());
+        }
+        for pass in &self.post_process.passes {
+            if let Some(path) = &pass.fragment_path {
+                result.push(path.clone());
+            }
+        }
+        result
+    }
+
+    /// Reload if hot reloading is on and a watched file changed. Errors are kept in reload_error to be shown on screen.
+    fn check_hot_reload(&mut self) {
+        let changed = match &mut self.hot_reload {
+            Some(watcher) => watcher.poll(),
+            None => return,
+        };
+        if changed.is_empty() {
+            return;
+        }
+
+        match self.reload() {
+            Ok(()) => {
+                if self.reload_error.take().is_some() {
+                    self.display.gl_window().window().set_title(&self.title);
+                }
+            }
+            Err(err) => {
+                let message = format!("Reload failed: {}", err);
+                self.display.gl_window().window().set_title(&message);
+                self.reload_error = Some(message);
+            }
+        }
+
+        // pick up newly added files and folders
+        let paths = self.watched_paths();
+        if let Some(watcher) = &mut self.hot_reload {
+            for path in paths {
+                watcher.watch(path);
+            }
+        }
+    }
+
+    /// Load the built-in font for the error overlay if it isn't loaded yet, so errors can be read whatever sheet failed to load.
+    #[cfg(feature = "default-font")]
+    fn load_overlay_font(&mut self) -> OozeResult<()> {
+        if self.overlay_font.is_none() {
+            let mut font = SpriteMap::new();
+            font.add_default_font(
+                &self.display,
+                None,
+                self.terminal.dims.glyph_size,
+                Scaling::Fit,
+            )?;
+            self.overlay_font = Some(font);
+        }
+
+        Ok(())
+    }
+
+    /// Without the built-in font the error overlay is drawn with this App's own sprites.
+    #[cfg(not(feature = "default-font"))]
+    fn load_overlay_font(&mut self) -> OozeResult<()> {
+        Ok(())
+    }
+
+    /// The sprites the error overlay is drawn with: the built-in font once it is loaded, otherwise this App's own sprites.
+    fn overlay_sprites(&self) -> &SpriteMap {
+        self.overlay_font.as_ref().unwrap_or(&self.sprites)
+    }
+
+    /// Build a Terminal showing the given error message in a bar along the top of the screen.
+    /// Characters without a matching sprite in the overlay's sprites are left out.
+    fn error_overlay(&self, message: &str) -> OozeResult<Terminal> {
+        let dims = self.terminal.dims;
+        let mut overlay = Terminal::new(dims);
+        let width = dims.term_size.x as usize;
+
+        let chars: Vec<char> = message.chars().collect();
+        for (row, line) in chars.chunks(width.max(1)).enumerate() {
+            if row as u32 >= dims.term_size.y {
+                break;
+            }
+            let y = dims.term_size.y - 1 - row as u32;
+            for x in 0..dims.term_size.x {
+                let id = line.get(x as usize).unwrap_or(&' ').to_string();
+                if self.overlay_sprites().contains(&id) {
+                    overlay.root_panel.place(
+                        x,
+                        y,
+                        &id,
+                        [1.0, 1.0, 1.0, 1.0],
+                        [0.5, 0.0, 0.0, 1.0],
+                    )?;
+                }
+            }
+        }
+
+        Ok(overlay)
+    }
+
+    /// Capture the current frame as an image, top row first.
+    pub fn capture(&self, source: CaptureSource) -> OozeResult<RgbaImage> {
+        match source {
+            CaptureSource::FrontBuffer => capture_front_buffer(&self.display),
+            CaptureSource::Terminal => render_terminal(&self.terminal, &self.sprites),
+        }
+    }
+
+    /// Save what is currently shown in the window to a png file.
+    pub fn screenshot(&self, path: &Path) -> OozeResult<()> {
+        save_png(&self.capture(CaptureSource::FrontBuffer)?, path)
+    }
+
+    /// Start recording every frame the App draws into a gif file or a folder of pngs.
+    /// A recording in progress is finished first.
+    pub fn start_recording(
+        &mut self,
+        path: &Path,
+        format: RecordFormat,
+        source: CaptureSource,
+        frame_delay: Duration,
+    ) -> OozeResult<()> {
+        self.stop_recording()?;
+        self.recorder = Some(Recorder::new(path, format, source, frame_delay)?);
+
+        Ok(())
+    }
+
+    /// Stop recording and finish the file. Returns the number of frames recorded.
+    pub fn stop_recording(&mut self) -> OozeResult<u32> {
+        match self.recorder.take() {
+            Some(recorder) => {
+                let frame_count = recorder.frame_count();
+                recorder.finish()?;
+                Ok(frame_count)
+            }
+            None => Ok(0),
+        }
+    }
+
+    /// Add the current frame to the recording, if there is one.
+    fn record_frame(&mut self) -> OozeResult<()> {
+        if let Some(source) = self.recorder.as_ref().map(|recorder| recorder.source) {
+            let frame = self.capture(source)?;
+            if let Some(recorder) = &mut self.recorder {
+                recorder.add_frame(frame)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Poll the window's events and return the ones ooze understands as InputEvents, in the order they happened.
+    /// When the window was resized, the Terminal is resized to the number of cells that fit in it.
+    pub fn poll_input(&mut self) -> Vec<InputEvent> {
+        let mut events = Vec::new();
+        let window_size = self
+            .display
+            .gl_window()
+            .get_inner_size()
+            .unwrap_or_else(|| glutin::dpi::LogicalSize::new(0.0, 0.0));
+        let term_size = self.terminal.dims.term_size;
+        self.events_loop.poll_events(|event| {
+            if let Some(input) = from_glutin(&event, window_size, term_size) {
+                events.push(input);
+            }
+        });
+
+        for event in &events {
+            if let InputEvent::Resized(width, height) = *event {
+                let glyph_size = self.terminal.dims.glyph_size;
+                let columns = width as f32 / (glyph_size.x as f32 * self.scale);
+                let rows = height as f32 / (glyph_size.y as f32 * self.scale);
+                self.terminal
+                    .resize(Point::new((columns as u32).max(1), (rows as u32).max(1)));
+            }
+        }
+        events
+    }
+
+    /// The Frame given to this App's callbacks.
+    fn frame(&mut self) -> Frame<'_> {
+        Frame::new(
+            &mut self.terminal,
+            &mut self.animator,
+            &mut self.particles,
+            &self.events,
+            &mut self.closed,
+        )
+    }
+
     /// Calls the given update callback set by the user, which should modify this App's terminal using information from the GameState.
     fn update_game(&mut self, game_state: &mut G) {
-        (self.update_game_callback)(self, game_state);
+        let callback = self.update_game_callback;
+        callback(&mut self.frame(), game_state);
     }
 
     fn handle_events(&mut self, game_state: &mut G) {
-        (self.handle_events_callback)(self, game_state);
+        let callback = self.handle_events_callback;
+        callback(&mut self.frame(), game_state);
     }
 
-    /// Draw this App's Terminal to the window.
-    fn draw(&self) -> OozeResult<()> {
+    /// Draw this App's Terminal to the window, through the post-processing chain if it has any enabled passes.
+    fn draw(&mut self) -> OozeResult<()> {
+        if self.reload_error.is_some() {
+            self.load_overlay_font()?;
+        }
+
+        let time = self.start_time.elapsed().as_secs_f32();
+        let (width, height) = self.display.get_framebuffer_dimensions();
+        self.glyph_uniforms.set("time", UniformData::Float(time));
+        self.glyph_uniforms.set(
+            "screen_size",
+            UniformData::Vec2([width as f32, height as f32]),
+        );
+
         let mut target = self.display.draw();
 
         target.clear_color(0.0, 0.0, 0.0, 1.0);
 
-        self.terminal
-            .draw(&mut target, &self.display, &self.program, &self.sprites)?;
+        let (terminal, display, program, sprites, uniforms) = (
+            &self.terminal,
+            &self.display,
+            &self.program,
+            &self.sprites,
+            &self.glyph_uniforms,
+        );
+        if self.post_process.is_active() {
+            self.post_process
+                .render(display, &mut target, time, |buffer| {
+                    terminal.draw(buffer, display, program, sprites, uniforms)
+                })?;
+        } else {
+            terminal.draw(&mut target, display, program, sprites, uniforms)?;
+        }
+
+        if let Some(message) = &self.reload_error {
+            self.error_overlay(message)?.draw(
+                &mut target,
+                display,
+                program,
+                self.overlay_sprites(),
+                uniforms,
+            )?;
+        }
 
         target.finish().unwrap();
 
@@ -105,43 +539,55 @@ impl<G: GameState> App<G> {
     /// Start this App's main loop. Draws the App, handles window events, and calls update on the GameState.
     pub fn run(&mut self, game_state: &mut G) -> OozeResult<()> {
         while !self.closed {
+            // move running animations and particles on to the current time
+            let time = self.start_time.elapsed().as_secs_f32();
+            self.animator.update(&mut self.terminal, time);
+            self.particles.update(&mut self.terminal, time);
+
             // clear, draw the terminal, and flip the window
             let start = Instant::now();
             self.draw()?;
             println!("{:?}", start.elapsed());
 
+            // Capture the frame if we're recording
+            self.record_frame()?;
+
+            // Reload changed resources in hot reload mode
+            self.check_hot_reload();
+
             // Handle all window events
+            self.events = self.poll_input();
             self.handle_events(game_state);
 
             // Tell the game_state to update itself
             self.update_game(game_state);
         }
 
+        // Finish the file of a recording still in progress
+        self.stop_recording()?;
+
         Ok(())
     }
 }
 
 /// Is the default upon App creation, does nothing.
-fn default_update_callback<G: GameState>(_app: &mut App<G>, _game_state: &mut G) {}
+fn default_update_callback<G: GameState>(_frame: &mut Frame, _game_state: &mut G) {}
 
 // Is the default upon App creation, instructs the App to close when the window gets a close event.
-fn default_handle_events_callback<G: GameState>(app: &mut App<G>, _game_state: &mut G) {
-    let mut events = Vec::new();
-    app.events_loop.poll_events(|ev| events.push(ev));
-
-    for event in events {
-        if let glutin::Event::WindowEvent {
-            event: window_event,
-            ..
-        } = event
-        {
-            if let glutin::WindowEvent::CloseRequested = window_event {
-                app.closed = true;
-            }
-        }
+fn default_handle_events_callback<G: GameState>(frame: &mut Frame, _game_state: &mut G) {
+    if frame.events.contains(&InputEvent::Close) {
+        frame.close();
     }
 }
 
+/// The folder relative resource paths are resolved against when an App is created.
+/// This is the OOZE_RESOURCE_ROOT environment variable if it is set, otherwise the working directory.
+fn default_resource_root() -> PathBuf {
+    env::var_os("OOZE_RESOURCE_ROOT")
+        .map(PathBuf::from)
+        .unwrap_or_default()
+}
+
 /// Creates and returns an event loop and a display, which manages window and OpenGL context
 fn init_window(
     width: usize,
@@ -153,7 +599,7 @@ fn init_window(
     let events_loop = glutin::EventsLoop::new();
     let window = glutin::WindowBuilder::new()
         .with_dimensions(size)
-        .with_resizable(false)
+        .with_resizable(true)
         .with_title(title);
     let context = glutin::ContextBuilder::new();
     let display = glium::Display::new(window, context, &events_loop)?;
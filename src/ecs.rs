@@ -0,0 +1,208 @@
+use specs::{Component, Join, VecStorage, World, WorldExt};
+use std::collections::HashMap;
+
+use crate::error::OozeResult;
+use crate::geometry::Point;
+use crate::terminal::{Glyph, Panel};
+
+/// Where an entity is, as a cell of the Panel it is rendered on.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Position {
+    pub x: u32,
+    pub y: u32,
+}
+
+impl Position {
+    /// Create a new Position.
+    pub fn new(x: u32, y: u32) -> Position {
+        Position { x, y }
+    }
+
+    /// This Position as a Point.
+    pub fn point(self) -> Point {
+        Point::new(self.x, self.y)
+    }
+}
+
+impl From<Point> for Position {
+    fn from(point: Point) -> Position {
+        Position::new(point.x, point.y)
+    }
+}
+
+impl Component for Position {
+    type Storage = VecStorage<Self>;
+}
+
+/// How an entity is drawn: the Glyph it shows and how it ranks against other entities on the same cell.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Renderable {
+    pub sprite_id: String,
+    pub fg_color: [f32; 4],
+    pub bg_color: [f32; 4],
+    /// Of the entities on a cell, the one with the highest render order is shown, e.g. 0 for items, 1 for monsters, 2 for the player.
+    pub render_order: i32,
+}
+
+impl Renderable {
+    /// Create a new Renderable with render order 0.
+    pub fn new(sprite_id: &str, fg_color: [f32; 4], bg_color: [f32; 4]) -> Renderable {
+        Renderable {
+            sprite_id: sprite_id.to_string(),
+            fg_color,
+            bg_color,
+            render_order: 0,
+        }
+    }
+
+    /// Returns a copy of this Renderable with the given render order.
+    pub fn with_render_order(self, render_order: i32) -> Renderable {
+        let mut new = self;
+        new.render_order = render_order;
+        new
+    }
+}
+
+impl Component for Renderable {
+    type Storage = VecStorage<Self>;
+}
+
+/// Register the Position and Renderable components with a World.
+pub fn register_components(world: &mut World) {
+    world.register::<Position>();
+    world.register::<Renderable>();
+}
+
+/// Draw every entity of the World with a Position and a Renderable on a Panel, see render_entities.
+pub fn render_world(world: &World, panel: &mut Panel) -> OozeResult<()> {
+    let positions = world.read_storage::<Position>();
+    let renderables = world.read_storage::<Renderable>();
+
+    render_entities(panel, (&positions, &renderables).join())
+}
+
+/// Clear a Panel and draw entities on it, for a Panel that only shows entities, above the map.
+/// Each cell shows the entity with the highest render order on it, the first one given wins ties. Entities outside the Panel are skipped.
+pub fn render_entities<'a, I>(panel: &mut Panel, entities: I) -> OozeResult<()>
+where
+    I: IntoIterator<Item = (&'a Position, &'a Renderable)>,
+{
+    let bounds = panel.rect();
+    let mut shown: HashMap<(u32, u32), &Renderable> = HashMap::new();
+    for (position, renderable) in entities {
+        if !bounds.contains_point(position.point()) {
+            continue;
+        }
+        let cell = shown.entry((position.x, position.y)).or_insert(renderable);
+        if renderable.render_order > cell.render_order {
+            *cell = renderable;
+        }
+    }
+
+    for column in &mut panel.contents {
+        for glyph in column.iter_mut() {
+            *glyph = Glyph::empty(glyph.location);
+        }
+    }
+    for ((x, y), renderable) in shown {
+        panel.place(
+            x,
+            y,
+            &renderable.sprite_id,
+            renderable.fg_color,
+            renderable.bg_color,
+        )?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::Dimensions;
+    use specs::Builder;
+
+    fn panel() -> Panel {
+        Panel::new(Dimensions::new(1, 1, 4, 3, 0, 0))
+    }
+
+    fn renderable(sprite_id: &str, render_order: i32) -> Renderable {
+        Renderable::new(sprite_id, [1.0; 4], [0.0, 0.0, 0.0, 1.0]).with_render_order(render_order)
+    }
+
+    fn sprite_at(panel: &Panel, x: u32, y: u32) -> String {
+        panel.get(Point::new(x, y)).unwrap().sprite_id.clone()
+    }
+
+    #[test]
+    fn highest_render_order_wins_and_ties_go_to_the_first() {
+        let mut panel = panel();
+        let (here, there) = (Position::new(1, 1), Position::new(2, 2));
+        let (item, potion, monster) = (
+            renderable("item", 0),
+            renderable("potion", 0),
+            renderable("monster", 1),
+        );
+
+        let entities = vec![
+            (&here, &item),
+            (&here, &potion),
+            (&there, &monster),
+            (&there, &item),
+        ];
+        render_entities(&mut panel, entities).unwrap();
+        assert_eq!(sprite_at(&panel, 1, 1), "item");
+        assert_eq!(sprite_at(&panel, 2, 2), "monster");
+
+        render_entities(&mut panel, vec![(&here, &potion), (&here, &item)]).unwrap();
+        assert_eq!(sprite_at(&panel, 1, 1), "potion");
+        // cells without entities are cleared
+        assert_eq!(sprite_at(&panel, 2, 2), "empty");
+    }
+
+    #[test]
+    fn entities_outside_the_panel_are_skipped() {
+        let mut panel = panel();
+        let player = renderable("player", 2);
+        let positions = [
+            Position::new(4, 0),
+            Position::new(0, 3),
+            Position::new(100, 100),
+            Position::new(3, 2),
+        ];
+
+        render_entities(
+            &mut panel,
+            positions.iter().map(|position| (position, &player)),
+        )
+        .unwrap();
+
+        let shown: Vec<&Glyph> = panel
+            .glyphs()
+            .into_iter()
+            .filter(|glyph| glyph.sprite_id == "player")
+            .collect();
+        assert_eq!(shown.len(), 1);
+        assert_eq!(shown[0].location, Point::new(3, 2));
+    }
+
+    #[test]
+    fn worlds_are_rendered() {
+        let mut world = World::new();
+        register_components(&mut world);
+        world
+            .create_entity()
+            .with(Position::new(0, 0))
+            .with(renderable("player", 2))
+            .build();
+        // not drawn without a Renderable
+        world.create_entity().with(Position::new(1, 0)).build();
+
+        let mut panel = panel();
+        render_world(&world, &mut panel).unwrap();
+
+        assert_eq!(sprite_at(&panel, 0, 0), "player");
+        assert_eq!(sprite_at(&panel, 1, 0), "empty");
+    }
+}
@@ -10,6 +10,10 @@ use glium;
 
 use toml;
 
+use serde_json;
+
+use bincode;
+
 pub type OozeResult<T> = Result<T, Box<dyn Error>>;
 
 #[derive(Debug)]
@@ -18,9 +22,15 @@ pub enum OozeError {
     ImageError(image::ImageError),
     TextureCreationError(glium::texture::TextureCreationError),
     TomlDeserializeError(toml::de::Error),
+    TomlSerializeError(toml::ser::Error),
+    JsonError(serde_json::Error),
+    BincodeError(bincode::Error),
     DrawError(glium::DrawError),
     DisplayCreationError(glium::backend::glutin::DisplayCreationError),
     BadColorError([f32; 4]),
+    SpriteConflictError(String),
+    BadFormatError(String),
+    PanelTreeError(String),
     OutOfBoundsError,
     PathError,
     GenericError,
@@ -33,9 +43,19 @@ impl fmt::Display for OozeError {
             OozeError::ImageError(err) => err.fmt(f),
             OozeError::TextureCreationError(err) => err.fmt(f),
             OozeError::TomlDeserializeError(err) => err.fmt(f),
+            OozeError::TomlSerializeError(err) => err.fmt(f),
+            OozeError::JsonError(err) => err.fmt(f),
+            OozeError::BincodeError(err) => err.fmt(f),
             OozeError::DrawError(err) => err.fmt(f),
             OozeError::DisplayCreationError(err) => err.fmt(f),
             OozeError::BadColorError(_c) => write!(f, "A color was badly formatted."),
+            OozeError::SpriteConflictError(id) => {
+                write!(f, "A sprite with the id \"{}\" was already loaded.", id)
+            }
+            OozeError::BadFormatError(msg) => write!(f, "A file was badly formatted: {}", msg),
+            OozeError::PanelTreeError(msg) => {
+                write!(f, "The Panel tree couldn't be changed: {}", msg)
+            }
             OozeError::OutOfBoundsError => write!(f, "Something was out of bounds."),
             OozeError::PathError => write!(f, "An error occured with a path."),
             OozeError::GenericError => write!(f, "Generic error occured with ooze."),
@@ -50,9 +70,15 @@ impl Error for OozeError {
             OozeError::ImageError(err) => err.description(),
             OozeError::TextureCreationError(err) => err.description(),
             OozeError::TomlDeserializeError(err) => err.description(),
+            OozeError::TomlSerializeError(err) => err.description(),
+            OozeError::JsonError(err) => err.description(),
+            OozeError::BincodeError(err) => err.description(),
             OozeError::DrawError(err) => err.description(),
             OozeError::DisplayCreationError(err) => err.description(),
             OozeError::BadColorError(_c) => "A badly formatted color",
+            OozeError::SpriteConflictError(_id) => "Conflicting sprite id",
+            OozeError::BadFormatError(_msg) => "Badly formatted file",
+            OozeError::PanelTreeError(_msg) => "Panel tree error",
             OozeError::OutOfBoundsError => "Out of bounds",
             OozeError::PathError => "Path error",
             OozeError::GenericError => "Generic ooze error",
@@ -84,6 +110,24 @@ impl From<toml::de::Error> for OozeError {
     }
 }
 
+impl From<toml::ser::Error> for OozeError {
+    fn from(err: toml::ser::Error) -> OozeError {
+        OozeError::TomlSerializeError(err)
+    }
+}
+
+impl From<serde_json::Error> for OozeError {
+    fn from(err: serde_json::Error) -> OozeError {
+        OozeError::JsonError(err)
+    }
+}
+
+impl From<bincode::Error> for OozeError {
+    fn from(err: bincode::Error) -> OozeError {
+        OozeError::BincodeError(err)
+    }
+}
+
 impl From<glium::DrawError> for OozeError {
     fn from(err: glium::DrawError) -> OozeError {
         OozeError::DrawError(err)
@@ -0,0 +1,416 @@
+use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
+use std::path::Path;
+
+use crate::error::OozeResult;
+use crate::geometry::Point;
+use crate::terminal::{load_from_file, save_to_file, Glyph, GlyphEffects, Panel};
+
+/// What the player knows about a cell of the map.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Visibility {
+    /// Never seen, drawn covered up.
+    Unseen,
+    /// Seen before but not now, drawn as it was last seen.
+    Seen,
+    /// In view, drawn as it is.
+    Visible,
+}
+
+/// Tracks which cells of a map Panel have been seen and what they looked like, and draws that as an overlay Panel above the map:
+/// unseen cells are covered, remembered cells show their last known Glyph faded and grey, visible cells let the map show through.
+/// Saves with the game like Panels do.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(into = "FogData", try_from = "FogData")]
+pub struct FogOfWar {
+    pub size: Point,
+    /// How much of their color remembered glyphs lose, from 0.0 to 1.0.
+    pub desaturation: f32,
+    /// Multiplies the colors of remembered glyphs.
+    pub brightness: f32,
+    /// The color unseen cells are covered with.
+    pub unseen_color: [f32; 4],
+
+    cells: Vec<Vec<Visibility>>,
+    memory: Vec<Vec<Option<Glyph>>>,
+}
+
+impl FogOfWar {
+    /// Create a new FogOfWar of the given size with every cell unseen, covered in black.
+    pub fn new(size: Point) -> FogOfWar {
+        let (width, height) = (size.x as usize, size.y as usize);
+        FogOfWar {
+            size,
+            desaturation: 0.8,
+            brightness: 0.5,
+            unseen_color: [0.0, 0.0, 0.0, 1.0],
+            cells: vec![vec![Visibility::Unseen; height]; width],
+            memory: vec![vec![None; height]; width],
+        }
+    }
+
+    /// Returns a copy of this FogOfWar with the given desaturation.
+    pub fn with_desaturation(self, desaturation: f32) -> FogOfWar {
+        let mut new = self;
+        new.desaturation = desaturation;
+        new
+    }
+
+    /// Returns a copy of this FogOfWar with the given brightness.
+    pub fn with_brightness(self, brightness: f32) -> FogOfWar {
+        let mut new = self;
+        new.brightness = brightness;
+        new
+    }
+
+    /// Returns a copy of this FogOfWar with the given unseen color.
+    pub fn with_unseen_color(self, unseen_color: [f32; 4]) -> FogOfWar {
+        let mut new = self;
+        new.unseen_color = unseen_color;
+        new
+    }
+
+    /// What is known about a cell. Cells outside the map are unseen.
+    pub fn visibility(&self, point: Point) -> Visibility {
+        self.cells
+            .get(point.x as usize)
+            .and_then(|column| column.get(point.y as usize))
+            .copied()
+            .unwrap_or(Visibility::Unseen)
+    }
+
+    /// Check if a cell is in view.
+    pub fn is_visible(&self, point: Point) -> bool {
+        self.visibility(point) == Visibility::Visible
+    }
+
+    /// Check if a cell is in view or was seen before.
+    pub fn is_explored(&self, point: Point) -> bool {
+        self.visibility(point) != Visibility::Unseen
+    }
+
+    /// The Glyph a cell had when it was last in view.
+    pub fn remembered(&self, point: Point) -> Option<&Glyph> {
+        self.memory
+            .get(point.x as usize)
+            .and_then(|column| column.get(point.y as usize))
+            .and_then(Option::as_ref)
+    }
+
+    /// Update the view: cells is_visible returns true for become visible and are remembered as they are on the map Panel,
+    /// cells that were visible before and aren't now become seen. is_visible could be a field of view or LightMap::is_lit.
+    pub fn update<F: Fn(Point) -> bool>(&mut self, map: &Panel, is_visible: F) {
+        for x in 0..self.size.x as usize {
+            for y in 0..self.size.y as usize {
+                let point = Point::new(x as u32, y as u32);
+                if is_visible(point) {
+                    self.cells[x][y] = Visibility::Visible;
+                    if let Ok(glyph) = map.get(point) {
+                        self.memory[x][y] = Some(Glyph {
+                            effects: GlyphEffects::default(),
+                            ..glyph.clone()
+                        });
+                    }
+                } else if self.cells[x][y] == Visibility::Visible {
+                    self.cells[x][y] = Visibility::Seen;
+                }
+            }
+        }
+    }
+
+    /// Make every cell visible and remember the whole map, e.g. for a magic map or debugging.
+    pub fn reveal_all(&mut self, map: &Panel) {
+        self.update(map, |_| true);
+    }
+
+    /// Forget everything, every cell becomes unseen.
+    pub fn forget(&mut self) {
+        let (width, height) = (self.size.x as usize, self.size.y as usize);
+        self.cells = vec![vec![Visibility::Unseen; height]; width];
+        self.memory = vec![vec![None; height]; width];
+    }
+
+    /// Create an empty Panel covering the map Panel to draw this FogOfWar on, to be added as a sub-panel of the map.
+    /// It is on a layer above the map's other sub-panels, so the things on them are hidden outside the view too.
+    pub fn overlay_panel(&self, map: &Panel) -> Panel {
+        let mut overlay = Panel::new(map.dims.with_offset(Point::new(0, 0)));
+        overlay.set_name("fog");
+        overlay.layer = map
+            .all_sub_panels()
+            .iter()
+            .map(|panel| panel.layer)
+            .max()
+            .unwrap_or(map.layer)
+            + 1;
+        overlay
+    }
+
+    /// Draw this FogOfWar on an overlay Panel, the cells of this FogOfWar being the cells of the Panel.
+    pub fn draw(&self, overlay: &mut Panel) {
+        for column in &mut overlay.contents {
+            for glyph in column.iter_mut() {
+                let location = glyph.location;
+                *glyph = match self.visibility(location) {
+                    Visibility::Visible => Glyph::empty(location),
+                    Visibility::Seen => match self.remembered(location) {
+                        Some(remembered) => self.faded(remembered),
+                        None => self.covered(location),
+                    },
+                    Visibility::Unseen => self.covered(location),
+                };
+            }
+        }
+    }
+
+    /// Save this FogOfWar to a .toml, .json or binary file.
+    pub fn save(&self, path: &Path) -> OozeResult<()> {
+        save_to_file(self, path)
+    }
+
+    /// Load a FogOfWar from a .toml, .json or binary file.
+    pub fn load(path: &Path) -> OozeResult<FogOfWar> {
+        load_from_file(path)
+    }
+
+    /// A remembered Glyph the way it is drawn, grey and dim over an opaque background so the map below doesn't show through.
+    fn faded(&self, remembered: &Glyph) -> Glyph {
+        let fade = |color: [f32; 4]| {
+            let grey = 0.299 * color[0] + 0.587 * color[1] + 0.114 * color[2];
+            let mut result = color;
+            for c in result.iter_mut().take(3) {
+                *c = ((*c + (grey - *c) * self.desaturation) * self.brightness).clamp(0.0, 1.0);
+            }
+            result
+        };
+
+        let mut bg_color = self.unseen_color;
+        let remembered_bg = fade(remembered.bg_color);
+        for (c, remembered_c) in bg_color.iter_mut().zip(&remembered_bg).take(3) {
+            *c = remembered_c * remembered_bg[3] + *c * (1.0 - remembered_bg[3]);
+        }
+        bg_color[3] = 1.0;
+
+        Glyph {
+            fg_color: fade(remembered.fg_color),
+            bg_color,
+            ..remembered.clone()
+        }
+    }
+
+    fn covered(&self, location: Point) -> Glyph {
+        let mut glyph = Glyph::empty(location);
+        glyph.bg_color = self.unseen_color;
+        glyph
+    }
+}
+
+// Saved with sizes as plain values and a flat list of remembered Glyphs, as toml needs plain values before tables and can't store empty cells.
+#[derive(Serialize, Deserialize)]
+struct FogData {
+    width: u32,
+    height: u32,
+    desaturation: f32,
+    brightness: f32,
+    unseen_color: [f32; 4],
+    cells: Vec<Vec<Visibility>>,
+    memory: Vec<Glyph>,
+}
+
+impl From<FogOfWar> for FogData {
+    fn from(fog: FogOfWar) -> FogData {
+        FogData {
+            width: fog.size.x,
+            height: fog.size.y,
+            desaturation: fog.desaturation,
+            brightness: fog.brightness,
+            unseen_color: fog.unseen_color,
+            cells: fog.cells,
+            memory: fog.memory.into_iter().flatten().flatten().collect(),
+        }
+    }
+}
+
+impl TryFrom<FogData> for FogOfWar {
+    type Error = String;
+
+    fn try_from(data: FogData) -> Result<FogOfWar, String> {
+        let size = Point::new(data.width, data.height);
+        let right_size = data.cells.len() == size.x as usize
+            && data
+                .cells
+                .iter()
+                .all(|column| column.len() == size.y as usize);
+        if !right_size {
+            return Err(format!(
+                "fog of war cells don't match its size of {}x{}",
+                size.x, size.y
+            ));
+        }
+
+        let mut fog = FogOfWar::new(size)
+            .with_desaturation(data.desaturation)
+            .with_brightness(data.brightness)
+            .with_unseen_color(data.unseen_color);
+        fog.cells = data.cells;
+        for glyph in data.memory {
+            let (x, y) = (glyph.location.x, glyph.location.y);
+            if x >= size.x || y >= size.y {
+                return Err(format!(
+                    "remembered glyph at ({}, {}) is outside the fog of war",
+                    x, y
+                ));
+            }
+            fog.memory[x as usize][y as usize] = Some(glyph);
+        }
+
+        Ok(fog)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::Dimensions;
+    use crate::terminal::SaveFormat;
+    use std::{env, fs};
+
+    const RED: [f32; 4] = [1.0, 0.0, 0.0, 1.0];
+    const BLACK: [f32; 4] = [0.0, 0.0, 0.0, 1.0];
+
+    /// A 3x1 map with a red wall in every cell.
+    fn map() -> Panel {
+        let mut map = Panel::new(Dimensions::new(1, 1, 3, 1, 0, 0));
+        for x in 0..3 {
+            map.place(x, 0, "wall", RED, BLACK).unwrap();
+        }
+        map
+    }
+
+    fn visibilities(fog: &FogOfWar) -> Vec<Visibility> {
+        (0..3).map(|x| fog.visibility(Point::new(x, 0))).collect()
+    }
+
+    #[test]
+    fn cells_go_from_unseen_to_visible_to_seen() {
+        let mut map = map();
+        let mut fog = FogOfWar::new(Point::new(3, 1));
+        assert_eq!(visibilities(&fog), vec![Visibility::Unseen; 3]);
+
+        fog.update(&map, |point| point.x == 0);
+        assert_eq!(
+            visibilities(&fog),
+            vec![Visibility::Visible, Visibility::Unseen, Visibility::Unseen]
+        );
+        fog.update(&map, |point| point.x == 1);
+        assert_eq!(
+            visibilities(&fog),
+            vec![Visibility::Seen, Visibility::Visible, Visibility::Unseen]
+        );
+        assert!(fog.is_explored(Point::new(0, 0)));
+        assert!(!fog.is_visible(Point::new(0, 0)));
+        assert!(fog.remembered(Point::new(2, 0)).is_none());
+        assert_eq!(fog.visibility(Point::new(3, 0)), Visibility::Unseen);
+
+        fog.forget();
+        assert_eq!(visibilities(&fog), vec![Visibility::Unseen; 3]);
+        assert!(fog.remembered(Point::new(1, 0)).is_none());
+
+        fog.reveal_all(&map);
+        assert_eq!(visibilities(&fog), vec![Visibility::Visible; 3]);
+
+        // remembered glyphs are kept as they were seen, without effects
+        map.place(2, 0, "floor", RED, BLACK).unwrap();
+        map.contents[2][0].effects.opacity = 0.5;
+        fog.update(&map, |point| point.x == 2);
+        assert_eq!(fog.remembered(Point::new(0, 0)).unwrap().sprite_id, "wall");
+        let floor = fog.remembered(Point::new(2, 0)).unwrap();
+        assert_eq!(floor.sprite_id, "floor");
+        assert_eq!(floor.effects, GlyphEffects::default());
+    }
+
+    #[test]
+    fn the_overlay_covers_fades_and_shows() {
+        let map = map();
+        let fog = {
+            let mut fog = FogOfWar::new(Point::new(3, 1))
+                .with_desaturation(1.0)
+                .with_brightness(0.5)
+                .with_unseen_color([0.0, 0.0, 0.1, 1.0]);
+            fog.update(&map, |point| point.x < 2);
+            fog.update(&map, |point| point.x == 1);
+            fog
+        };
+        let mut overlay = fog.overlay_panel(&map);
+        fog.draw(&mut overlay);
+
+        let seen = overlay.get(Point::new(0, 0)).unwrap();
+        let grey = 0.299 * 0.5;
+        assert_eq!(seen.sprite_id, "wall");
+        for c in seen.fg_color.iter().take(3) {
+            assert!((c - grey).abs() < 1e-6);
+        }
+        assert_eq!(seen.bg_color, BLACK);
+        assert_eq!(
+            *overlay.get(Point::new(1, 0)).unwrap(),
+            Glyph::empty(Point::new(1, 0))
+        );
+        let unseen = overlay.get(Point::new(2, 0)).unwrap();
+        assert_eq!(unseen.sprite_id, "empty");
+        assert_eq!(unseen.bg_color, [0.0, 0.0, 0.1, 1.0]);
+    }
+
+    #[test]
+    fn the_overlay_is_above_every_sub_panel() {
+        let mut map = map();
+        let mut items = Panel::new(Dimensions::new(1, 1, 1, 1, 0, 0));
+        items.layer = 3;
+        map.add_sub_panel(items).unwrap();
+
+        let overlay = FogOfWar::new(Point::new(3, 1)).overlay_panel(&map);
+        assert_eq!(overlay.layer, 4);
+        assert_eq!(overlay.dims, map.dims);
+    }
+
+    #[test]
+    fn fog_round_trip() {
+        let map = map();
+        let mut fog = FogOfWar::new(Point::new(3, 1)).with_brightness(0.3);
+        fog.update(&map, |point| point.x == 0);
+        fog.update(&map, |point| point.x == 1);
+
+        for extension in &["toml", "json", "bin"] {
+            let path = env::temp_dir().join(format!("ooze_fog_round_trip.{}", extension));
+            fog.save(&path).unwrap();
+            let loaded = FogOfWar::load(&path).unwrap();
+            fs::remove_file(&path).unwrap();
+
+            assert_eq!(loaded, fog);
+        }
+    }
+
+    #[test]
+    fn fog_that_does_not_fit_its_size_is_not_loaded() {
+        let data = |width, memory| FogData {
+            width,
+            height: 1,
+            desaturation: 0.8,
+            brightness: 0.5,
+            unseen_color: BLACK,
+            cells: vec![vec![Visibility::Seen]; 2],
+            memory,
+        };
+        let load = |data: FogData| {
+            let bytes = SaveFormat::Json.to_bytes(&data).unwrap();
+            SaveFormat::Json.from_bytes::<FogOfWar>(&bytes)
+        };
+        let glyph = |x| Glyph::new(Point::new(x, 0), RED, BLACK, "wall".to_string()).unwrap();
+
+        let loaded = load(data(2, vec![glyph(1)])).unwrap();
+        assert_eq!(loaded.remembered(Point::new(1, 0)), Some(&glyph(1)));
+
+        let error = load(data(2, vec![glyph(2)])).unwrap_err();
+        assert!(error.to_string().contains("outside the fog of war"));
+        let error = load(data(3, Vec::new())).unwrap_err();
+        assert!(error.to_string().contains("don't match its size of 3x1"));
+    }
+}
@@ -0,0 +1,7 @@
+mod cp437;
+mod rexpaint;
+mod text;
+
+pub use cp437::*;
+pub use rexpaint::*;
+pub use text::*;
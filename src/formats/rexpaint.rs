@@ -0,0 +1,284 @@
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::Path;
+
+use crate::error::{OozeError, OozeResult};
+use crate::formats::{char_to_cp437, cp437_to_char};
+use crate::geometry::{Dimensions, Point};
+use crate::terminal::{Glyph, Panel};
+
+/// The background color REXPaint uses to mark a cell as transparent.
+pub const XP_TRANSPARENT: [u8; 3] = [255, 0, 255];
+
+// the version number REXPaint writes at the start of its files
+const XP_VERSION: i32 = -1;
+
+// a cell is a CP437 code and two colors
+const XP_CELL_BYTES: u64 = 10;
+
+/// The sprite id for a CP437 code: the character itself, matching the built-in font sheet.
+/// Code 0 is drawn blank by REXPaint, so it becomes a space.
+pub fn default_sprite_id(code: u32) -> String {
+    match code {
+        0 => " ".to_string(),
+        1..=255 => cp437_to_char(code as u8).to_string(),
+        _ => "?".to_string(),
+    }
+}
+
+/// The CP437 code for a sprite id: single characters map to their code, anything else to '?'.
+pub fn default_cp437_code(sprite_id: &str) -> u32 {
+    let mut chars = sprite_id.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => char_to_cp437(c).map_or(u32::from(b'?'), u32::from),
+        _ if sprite_id == "empty" => 0,
+        _ => u32::from(b'?'),
+    }
+}
+
+/// Load a REXPaint .xp file as a Panel tree. The first layer becomes the returned Panel and every other layer one of its sub-panels, in order.
+/// Cells with the transparent background color are left empty. CP437 codes are turned into sprite ids by sprite_id.
+pub fn load_xp<F: Fn(u32) -> String>(
+    path: &Path,
+    glyph_size: Point,
+    sprite_id: F,
+) -> OozeResult<Panel> {
+    let mut data = Vec::new();
+    GzDecoder::new(File::open(path)?).read_to_end(&mut data)?;
+    parse_xp(&data, glyph_size, sprite_id)
+}
+
+/// Build a Panel tree from the uncompressed contents of a .xp file.
+pub fn parse_xp<F: Fn(u32) -> String>(
+    data: &[u8],
+    glyph_size: Point,
+    sprite_id: F,
+) -> OozeResult<Panel> {
+    let mut reader = XpReader { data, position: 0 };
+    let _version = reader.read_i32()?;
+    let layer_count = reader.read_i32()?;
+    if layer_count < 1 {
+        return Err(bad_format("an .xp file needs at least one layer"));
+    }
+    // every layer has its size and at least one cell
+    if layer_count as u64 * (8 + XP_CELL_BYTES) > reader.remaining() {
+        return Err(bad_format("the .xp file ended too early"));
+    }
+
+    let mut layers = Vec::with_capacity(layer_count as usize);
+    for layer in 0..layer_count as usize {
+        let width = reader.read_i32()?;
+        let height = reader.read_i32()?;
+        if width < 1 || height < 1 {
+            return Err(bad_format("a layer has no cells"));
+        }
+        let (width, height) = (width as u32, height as u32);
+        if u64::from(width) * u64::from(height) * XP_CELL_BYTES > reader.remaining() {
+            return Err(bad_format("the .xp file ended too early"));
+        }
+
+        let dims = Dimensions::from_sizes(glyph_size, Point::new(width, height), Point::new(0, 0));
+        let mut panel = Panel::new(dims);
+        panel.layer = layer;
+
+        // cells are stored column by column, each from the top down
+        for x in 0..width {
+            for row in 0..height {
+                let code = reader.read_i32()?;
+                let fg = reader.read_rgb()?;
+                let bg = reader.read_rgb()?;
+                if bg == XP_TRANSPARENT {
+                    continue;
+                }
+
+                let point = Point::new(x, height - 1 - row);
+                let glyph = Glyph::new(point, to_color(fg), to_color(bg), sprite_id(code as u32))?;
+                panel.set(point, glyph)?;
+            }
+        }
+        layers.push(panel);
+    }
+
+    let mut root = layers.remove(0);
+    root.sub_panels = layers;
+
+    Ok(root)
+}
+
+/// Save a Panel tree as a REXPaint .xp file, one layer per Panel in the tree.
+/// Every layer is the size of the given Panel; sub-panels are placed at their offset and cut off at its edges.
+/// Sprite ids are turned into CP437 codes by cp437_code.
+pub fn save_xp<F: Fn(&str) -> u32>(panel: &Panel, path: &Path, cp437_code: F) -> OozeResult<()> {
+    let mut encoder = GzEncoder::new(File::create(path)?, Compression::default());
+    encoder.write_all(&xp_bytes(panel, cp437_code))?;
+    encoder.finish()?;
+
+    Ok(())
+}
+
+/// The uncompressed contents of a .xp file for a Panel tree.
+pub fn xp_bytes<F: Fn(&str) -> u32>(panel: &Panel, cp437_code: F) -> Vec<u8> {
+    let (width, height) = (panel.dims.term_size.x, panel.dims.term_size.y);
+    let origin = panel.dims.offset;
+    let layers = panel.all_sub_panels();
+
+    let mut data = Vec::new();
+    data.extend_from_slice(&XP_VERSION.to_le_bytes());
+    data.extend_from_slice(&(layers.len() as i32).to_le_bytes());
+
+    for layer in layers {
+        data.extend_from_slice(&(width as i32).to_le_bytes());
+        data.extend_from_slice(&(height as i32).to_le_bytes());
+
+        for x in 0..width {
+            for row in 0..height {
+                let y = height - 1 - row;
+                let glyph = layer_glyph(layer, origin, Point::new(x, y));
+                match glyph {
+                    Some(glyph) if !glyph.fully_transparent() => {
+                        data.extend_from_slice(
+                            &(cp437_code(&glyph.sprite_id) as i32).to_le_bytes(),
+                        );
+                        data.extend_from_slice(&to_rgb(glyph.fg_color));
+                        data.extend_from_slice(&to_rgb(glyph.bg_color));
+                    }
+                    _ => {
+                        data.extend_from_slice(&0i32.to_le_bytes());
+                        data.extend_from_slice(&[0, 0, 0]);
+                        data.extend_from_slice(&XP_TRANSPARENT);
+                    }
+                }
+            }
+        }
+    }
+
+    data
+}
+
+impl Panel {
+    /// Load a REXPaint .xp file as a Panel tree, using the characters of the built-in font as sprite ids.
+    pub fn load_xp(path: &Path, glyph_size: Point) -> OozeResult<Panel> {
+        load_xp(path, glyph_size, default_sprite_id)
+    }
+
+    /// Save this Panel tree as a REXPaint .xp file, mapping single character sprite ids to CP437 codes.
+    pub fn save_xp(&self, path: &Path) -> OozeResult<()> {
+        save_xp(self, path, default_cp437_code)
+    }
+}
+
+/// The Glyph of layer at the point of the root Panel whose offset is origin, if the layer covers it.
+fn layer_glyph(layer: &Panel, origin: Point, point: Point) -> Option<&Glyph> {
+    let x = (origin.x + point.x).checked_sub(layer.dims.offset.x)?;
+    let y = (origin.y + point.y).checked_sub(layer.dims.offset.y)?;
+    layer.get(Point::new(x, y)).ok()
+}
+
+fn to_color(rgb: [u8; 3]) -> [f32; 4] {
+    [
+        f32::from(rgb[0]) / 255.0,
+        f32::from(rgb[1]) / 255.0,
+        f32::from(rgb[2]) / 255.0,
+        1.0,
+    ]
+}
+
+fn to_rgb(color: [f32; 4]) -> [u8; 3] {
+    [
+        (color[0] * 255.0).round() as u8,
+        (color[1] * 255.0).round() as u8,
+        (color[2] * 255.0).round() as u8,
+    ]
+}
+
+fn bad_format(message: &str) -> Box<OozeError> {
+    Box::new(OozeError::BadFormatError(message.to_string()))
+}
+
+/// Reads the little-endian values of a .xp file in order.
+struct XpReader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> XpReader<'a> {
+    fn remaining(&self) -> u64 {
+        (self.data.len() - self.position) as u64
+    }
+
+    fn take(&mut self, count: usize) -> OozeResult<&'a [u8]> {
+        if self.position + count > self.data.len() {
+            return Err(bad_format("the .xp file ended too early"));
+        }
+        let bytes = &self.data[self.position..self.position + count];
+        self.position += count;
+
+        Ok(bytes)
+    }
+
+    fn read_i32(&mut self) -> OozeResult<i32> {
+        let bytes = self.take(4)?;
+        Ok(i32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    fn read_rgb(&mut self) -> OozeResult<[u8; 3]> {
+        let bytes = self.take(3)?;
+        Ok([bytes[0], bytes[1], bytes[2]])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn xp_round_trip() {
+        let glyph_size = Point::new(8, 8);
+        let mut panel = Panel::new(Dimensions::from_sizes(
+            glyph_size,
+            Point::new(4, 3),
+            Point::new(0, 0),
+        ));
+        panel
+            .place(0, 2, "@", [1.0, 1.0, 0.0, 1.0], [0.0, 0.0, 1.0, 1.0])
+            .unwrap();
+        panel
+            .place(3, 0, "#", [1.0, 1.0, 1.0, 1.0], [0.0, 0.0, 0.0, 1.0])
+            .unwrap();
+        let mut sub_panel = Panel::new(Dimensions::from_sizes(
+            glyph_size,
+            Point::new(2, 2),
+            Point::new(1, 1),
+        ));
+        sub_panel
+            .place(1, 0, "g", [0.0, 1.0, 0.0, 1.0], [0.0, 0.0, 0.0, 1.0])
+            .unwrap();
+        panel.add_sub_panel(sub_panel).unwrap();
+
+        let data = xp_bytes(&panel, default_cp437_code);
+        let loaded = parse_xp(&data, glyph_size, default_sprite_id).unwrap();
+
+        assert_eq!(loaded.dims, panel.dims);
+        assert_eq!(loaded.contents, panel.contents);
+        assert_eq!(loaded.sub_panels.len(), 1);
+        let layer = &loaded.sub_panels[0];
+        assert_eq!(layer.layer, 1);
+        assert_eq!(layer.dims.term_size, panel.dims.term_size);
+        let glyph = layer.get(Point::new(2, 1)).unwrap();
+        assert_eq!(glyph.sprite_id, "g");
+        assert_eq!(glyph.fg_color, [0.0, 1.0, 0.0, 1.0]);
+        assert!(layer.get(Point::new(1, 1)).unwrap().fully_transparent());
+    }
+
+    #[test]
+    fn xp_too_short() {
+        let mut data = Vec::new();
+        for value in &[XP_VERSION, 1, 10_000, 10_000, 0] {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+        assert!(parse_xp(&data, Point::new(8, 8), default_sprite_id).is_err());
+    }
+}
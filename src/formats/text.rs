@@ -0,0 +1,306 @@
+use std::borrow::Cow;
+use std::fmt::Write;
+
+use crate::geometry::Point;
+use crate::terminal::{Drawable, Glyph, Panel, Terminal};
+
+/// The character for a sprite id: single characters stand for themselves, "empty" is a space and anything else is '?'.
+pub fn sprite_char(sprite_id: &str) -> char {
+    let mut chars = sprite_id.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => c,
+        _ if sprite_id == "empty" => ' ',
+        _ => '?',
+    }
+}
+
+/// One cell of a TextGrid: the character shown on top and the colors it ends up with.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TextCell {
+    pub character: char,
+    pub fg_color: [u8; 3],
+    pub bg_color: [u8; 3],
+}
+
+/// The visible contents of a Terminal or Panel tree flattened to one character per cell, with rows from the top down.
+/// Layers are combined like they are drawn: backgrounds are alpha blended over black and the top visible character wins.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TextGrid {
+    pub width: u32,
+    pub height: u32,
+    pub rows: Vec<Vec<TextCell>>,
+}
+
+impl TextGrid {
+    /// Flatten everything drawn on a Terminal, turning sprite ids into characters with to_char.
+    /// FloatingSprites are shown on the cell under their center, ignoring their rotation and scale.
+    pub fn from_terminal<F: Fn(&str) -> char>(terminal: &Terminal, to_char: F) -> TextGrid {
+        let glyphs = terminal
+            .drawables()
+            .into_iter()
+            .filter_map(|drawable| match drawable {
+                Drawable::Glyph(glyph, point) => Some((Cow::Borrowed(glyph), point)),
+                Drawable::Sprite(sprite) => {
+                    let cell = sprite.cell()?;
+                    let mut glyph = Glyph::empty(cell);
+                    glyph.sprite_id = sprite.sprite_id.clone();
+                    glyph.fg_color = sprite.tint;
+                    glyph.bg_color = sprite.bg_color;
+                    Some((Cow::Owned(glyph), cell))
+                }
+            })
+            .collect();
+        TextGrid::from_glyphs(glyphs, terminal.dims.term_size, Point::new(0, 0), to_char)
+    }
+
+    /// Flatten a Panel and its sub-panels, cut off at the edges of the Panel.
+    pub fn from_panel<F: Fn(&str) -> char>(panel: &Panel, to_char: F) -> TextGrid {
+        let glyphs = panel
+            .collect_drawable_glyphs()
+            .into_iter()
+            .map(|(glyph, point, _layer)| (Cow::Borrowed(glyph), point))
+            .collect();
+        TextGrid::from_glyphs(glyphs, panel.dims.term_size, panel.dims.offset, to_char)
+    }
+
+    /// Combine (Glyph, final_point) pairs in drawing order into a grid of size cells whose bottom-left is at origin.
+    fn from_glyphs<F: Fn(&str) -> char>(
+        glyphs: Vec<(Cow<Glyph>, Point)>,
+        size: Point,
+        origin: Point,
+        to_char: F,
+    ) -> TextGrid {
+        let (width, height) = (size.x as usize, size.y as usize);
+        let mut chars = vec![vec![' '; width]; height];
+        let mut fg_colors = vec![vec![[1.0; 3]; width]; height];
+        let mut bg_colors = vec![vec![[0.0; 3]; width]; height];
+
+        for (glyph, point) in glyphs {
+            let x = match point.x.checked_sub(origin.x) {
+                Some(x) if x < size.x => x as usize,
+                _ => continue,
+            };
+            let y = match point.y.checked_sub(origin.y) {
+                Some(y) if y < size.y => y as usize,
+                _ => continue,
+            };
+            // rows are stored from the top down
+            let row = height - 1 - y;
+
+            let (fg_color, bg_color) = (glyph.drawn_fg_color(), glyph.drawn_bg_color());
+            let alpha = bg_color[3];
+            for (i, c) in bg_colors[row][x].iter_mut().enumerate() {
+                *c = bg_color[i] * alpha + *c * (1.0 - alpha);
+            }
+            if glyph.is_opaque() {
+                chars[row][x] = ' ';
+            }
+
+            let character = to_char(&glyph.sprite_id);
+            if fg_color[3] > 0.0 && character != ' ' {
+                chars[row][x] = character;
+                fg_colors[row][x] = [fg_color[0], fg_color[1], fg_color[2]];
+            }
+        }
+
+        let rows = (0..height)
+            .map(|row| {
+                (0..width)
+                    .map(|x| TextCell {
+                        character: chars[row][x],
+                        fg_color: to_rgb(fg_colors[row][x]),
+                        bg_color: to_rgb(bg_colors[row][x]),
+                    })
+                    .collect()
+            })
+            .collect();
+
+        TextGrid {
+            width: size.x,
+            height: size.y,
+            rows,
+        }
+    }
+
+    /// Only the characters, one line per row.
+    pub fn to_plain_text(&self) -> String {
+        let lines: Vec<String> = self
+            .rows
+            .iter()
+            .map(|row| row.iter().map(|cell| cell.character).collect())
+            .collect();
+        lines.join("\n")
+    }
+
+    /// The characters with 24-bit ANSI color escape codes, one line per row. Colors are reset at the end of every line.
+    pub fn to_ansi(&self) -> String {
+        let mut result = String::new();
+        for row in &self.rows {
+            let mut current: Option<([u8; 3], [u8; 3])> = None;
+            for cell in row {
+                let colors = (cell.fg_color, cell.bg_color);
+                if current != Some(colors) {
+                    let (fg, bg) = colors;
+                    write!(
+                        result,
+                        "\x1b[38;2;{};{};{}m\x1b[48;2;{};{};{}m",
+                        fg[0], fg[1], fg[2], bg[0], bg[1], bg[2]
+                    )
+                    .unwrap();
+                    current = Some(colors);
+                }
+                result.push(cell.character);
+            }
+            result.push_str("\x1b[0m\n");
+        }
+        result
+    }
+
+    /// A standalone HTML snippet: a <pre> block with a colored <span> for every run of cells with the same colors.
+    pub fn to_html(&self) -> String {
+        let mut result = String::from(
+            "<pre style=\"font-family: monospace; line-height: 1; background-color: #000000;\">\n",
+        );
+        for row in &self.rows {
+            let mut start = 0;
+            while start < row.len() {
+                let colors = (row[start].fg_color, row[start].bg_color);
+                let end = row[start..]
+                    .iter()
+                    .position(|cell| (cell.fg_color, cell.bg_color) != colors)
+                    .map_or(row.len(), |length| start + length);
+
+                write!(
+                    result,
+                    "<span style=\"color: {}; background-color: {};\">",
+                    hex_color(colors.0),
+                    hex_color(colors.1)
+                )
+                .unwrap();
+                for cell in &row[start..end] {
+                    match cell.character {
+                        '&' => result.push_str("&amp;"),
+                        '<' => result.push_str("&lt;"),
+                        '>' => result.push_str("&gt;"),
+                        '"' => result.push_str("&quot;"),
+                        c => result.push(c),
+                    }
+                }
+                result.push_str("</span>");
+                start = end;
+            }
+            result.push('\n');
+        }
+        result.push_str("</pre>\n");
+        result
+    }
+}
+
+impl Terminal {
+    /// What this Terminal shows as plain characters, using the sprite ids of the built-in font.
+    pub fn to_plain_text(&self) -> String {
+        TextGrid::from_terminal(self, sprite_char).to_plain_text()
+    }
+
+    /// What this Terminal shows as text with 24-bit ANSI colors.
+    pub fn to_ansi(&self) -> String {
+        TextGrid::from_terminal(self, sprite_char).to_ansi()
+    }
+
+    /// What this Terminal shows as a standalone HTML snippet.
+    pub fn to_html(&self) -> String {
+        TextGrid::from_terminal(self, sprite_char).to_html()
+    }
+}
+
+impl Panel {
+    /// This Panel tree as plain characters, using the sprite ids of the built-in font.
+    pub fn to_plain_text(&self) -> String {
+        TextGrid::from_panel(self, sprite_char).to_plain_text()
+    }
+
+    /// This Panel tree as text with 24-bit ANSI colors.
+    pub fn to_ansi(&self) -> String {
+        TextGrid::from_panel(self, sprite_char).to_ansi()
+    }
+
+    /// This Panel tree as a standalone HTML snippet.
+    pub fn to_html(&self) -> String {
+        TextGrid::from_panel(self, sprite_char).to_html()
+    }
+}
+
+fn to_rgb(color: [f32; 3]) -> [u8; 3] {
+    [
+        (color[0] * 255.0).round() as u8,
+        (color[1] * 255.0).round() as u8,
+        (color[2] * 255.0).round() as u8,
+    ]
+}
+
+fn hex_color(rgb: [u8; 3]) -> String {
+    format!("#{:02x}{:02x}{:02x}", rgb[0], rgb[1], rgb[2])
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::geometry::{Dimensions, Point};
+    use crate::terminal::{Panel, Terminal};
+
+    // a white on black background, a red on blue panel over it and a green glyph with a clear background over that
+    fn overlapping() -> Terminal {
+        let dims = Dimensions::new(8, 8, 3, 2, 0, 0);
+        let mut terminal = Terminal::new(dims);
+        terminal
+            .root_panel
+            .fill_with(".", [1.0, 1.0, 1.0, 1.0], [0.0, 0.0, 0.0, 1.0])
+            .unwrap();
+
+        let mut middle = Panel::new(dims.copy_for_panel(Point::new(2, 2), Point::new(1, 0)));
+        middle.set_layer(1);
+        middle
+            .fill_with("a", [1.0, 0.0, 0.0, 1.0], [0.0, 0.0, 1.0, 1.0])
+            .unwrap();
+        let mut top = Panel::new(dims.copy_for_panel(Point::new(1, 1), Point::new(2, 1)));
+        top.set_layer(2);
+        top.place(0, 0, "b", [0.0, 1.0, 0.0, 1.0], [0.0, 0.0, 0.0, 0.0])
+            .unwrap();
+        terminal.root_panel.add_sub_panel(top).unwrap();
+        terminal.root_panel.add_sub_panel(middle).unwrap();
+
+        terminal
+    }
+
+    #[test]
+    fn plain_text() {
+        assert_eq!(overlapping().to_plain_text(), ".ab\n.aa");
+    }
+
+    #[test]
+    fn ansi() {
+        let white = "\x1b[38;2;255;255;255m\x1b[48;2;0;0;0m";
+        let red = "\x1b[38;2;255;0;0m\x1b[48;2;0;0;255m";
+        let green = "\x1b[38;2;0;255;0m\x1b[48;2;0;0;255m";
+        let expected = format!(
+            "{white}.{red}a{green}b\x1b[0m\n{white}.{red}aa\x1b[0m\n",
+            white = white,
+            red = red,
+            green = green
+        );
+        assert_eq!(overlapping().to_ansi(), expected);
+    }
+
+    #[test]
+    fn html() {
+        let expected = concat!(
+            "<pre style=\"font-family: monospace; line-height: 1; background-color: #000000;\">\n",
+            "<span style=\"color: #ffffff; background-color: #000000;\">.</span>",
+            "<span style=\"color: #ff0000; background-color: #0000ff;\">a</span>",
+            "<span style=\"color: #00ff00; background-color: #0000ff;\">b</span>\n",
+            "<span style=\"color: #ffffff; background-color: #000000;\">.</span>",
+            "<span style=\"color: #ff0000; background-color: #0000ff;\">aa</span>\n",
+            "</pre>\n",
+        );
+        assert_eq!(overlapping().to_html(), expected);
+    }
+}
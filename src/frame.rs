@@ -0,0 +1,46 @@
+use crate::animation::{Animator, ParticleSystem};
+use crate::input::InputEvent;
+use crate::terminal::Terminal;
+
+/// The signature of the update and event callbacks, the same for App and TtyApp so one game runs in a window or a text terminal.
+pub type GameCallback<G> = fn(&mut Frame, &mut G);
+
+/// What the update and event callbacks of an App or TtyApp get to work with each frame.
+pub struct Frame<'a> {
+    pub terminal: &'a mut Terminal,
+    pub animator: &'a mut Animator,
+    pub particles: &'a mut ParticleSystem,
+    /// The input events since the last frame, in the order they happened.
+    pub events: &'a [InputEvent],
+
+    closed: &'a mut bool,
+}
+
+impl<'a> Frame<'a> {
+    /// Create a new Frame, closed is set when a callback closes the App.
+    pub fn new(
+        terminal: &'a mut Terminal,
+        animator: &'a mut Animator,
+        particles: &'a mut ParticleSystem,
+        events: &'a [InputEvent],
+        closed: &'a mut bool,
+    ) -> Frame<'a> {
+        Frame {
+            terminal,
+            animator,
+            particles,
+            events,
+            closed,
+        }
+    }
+
+    /// Stop the App's main loop after this frame.
+    pub fn close(&mut self) {
+        *self.closed = true;
+    }
+
+    /// Whether the App will stop after this frame.
+    pub fn is_closed(&self) -> bool {
+        *self.closed
+    }
+}
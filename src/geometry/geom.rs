@@ -1,7 +1,9 @@
+use serde::{Deserialize, Serialize};
+
 use crate::graphics::Vertex;
 
 /// A 2D point with x and y both positive integers.
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Point {
     pub x: u32,
     pub y: u32,
@@ -15,29 +17,24 @@ impl Point {
 
     /// Calculate the vertices for a quad on the screen, returns as [top-left, top-right, bottom-left, bottom-right] where self is at bottom-left.
     pub fn screen_verts(self, terminal_dims: Dimensions) -> [Vertex; 4] {
+        self.shifted_screen_verts([0.0, 0.0], terminal_dims)
+    }
+
+    /// Calculate the vertices for a quad on the screen moved by shift cells, see Point::screen_verts.
+    pub fn shifted_screen_verts(self, shift: [f32; 2], terminal_dims: Dimensions) -> [Vertex; 4] {
+        let x = self.x as f32 + shift[0];
+        let y = self.y as f32 + shift[1];
         [
-            Vertex::from_arrays(
-                self.plus(Point::new(0, 1)).to_screen(terminal_dims),
-                [0.0, 1.0],
-            ),
-            Vertex::from_arrays(
-                self.plus(Point::new(1, 1)).to_screen(terminal_dims),
-                [1.0, 1.0],
-            ),
-            Vertex::from_arrays(self.to_screen(terminal_dims), [0.0, 0.0]),
-            Vertex::from_arrays(
-                self.plus(Point::new(1, 0)).to_screen(terminal_dims),
-                [1.0, 0.0],
-            ),
+            Vertex::from_arrays(cell_to_screen(x, y + 1.0, terminal_dims), [0.0, 1.0]),
+            Vertex::from_arrays(cell_to_screen(x + 1.0, y + 1.0, terminal_dims), [1.0, 1.0]),
+            Vertex::from_arrays(cell_to_screen(x, y, terminal_dims), [0.0, 0.0]),
+            Vertex::from_arrays(cell_to_screen(x + 1.0, y, terminal_dims), [1.0, 0.0]),
         ]
     }
 
     /// Converts "terminal" coordinates to OpenGL screen coordinates. (i.e. from [0, terminal_size - 1] integer space to [-1, 1] float space)
     pub fn to_screen(self, terminal_dims: Dimensions) -> [f32; 2] {
-        [
-            2.0 * ((self.x as f32) / terminal_dims.term_size.x as f32) - 1.0,
-            2.0 * ((self.y as f32) / terminal_dims.term_size.y as f32) - 1.0,
-        ]
+        cell_to_screen(self.x as f32, self.y as f32, terminal_dims)
     }
 
     /// Adds points like vectors and returns a new point
@@ -48,10 +45,26 @@ impl Point {
             y: self.y + other.y,
         }
     }
+
+    /// Subtracts points like vectors and returns a new point, or None if it would be below 0
+    pub fn checked_minus(self, other: Point) -> Option<Point> {
+        Some(Point {
+            x: self.x.checked_sub(other.x)?,
+            y: self.y.checked_sub(other.y)?,
+        })
+    }
+}
+
+/// Converts fractional "terminal" coordinates to OpenGL screen coordinates, see Point::to_screen.
+pub fn cell_to_screen(x: f32, y: f32, terminal_dims: Dimensions) -> [f32; 2] {
+    [
+        2.0 * (x / terminal_dims.term_size.x as f32) - 1.0,
+        2.0 * (y / terminal_dims.term_size.y as f32) - 1.0,
+    ]
 }
 
 /// A 2D rectangle in positive integer coordinates.
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Rect {
     pub bottom_left: Point,
     pub size: Point,
@@ -98,7 +111,7 @@ impl Rect {
 }
 
 /// Dimensions for the creation of window-like objects.
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Dimensions {
     pub glyph_size: Point,
     pub term_size: Point,
@@ -0,0 +1,464 @@
+use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
+
+use crate::geometry::{Point, Rect};
+
+/// Which way a split lays out its slots: left to right, or top to bottom.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Direction {
+    Horizontal,
+    Vertical,
+}
+
+/// How much room a slot of a split gets along the split's direction.
+/// Saved as a string: "6" for Fixed(6), "25%" for Percent(25) and "1fr" for Fill(1).
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(into = "String", try_from = "String")]
+pub enum Constraint {
+    /// Exactly this many cells, if there is room.
+    Fixed(u32),
+    /// This percentage of the room inside the padding.
+    Percent(u32),
+    /// A share of whatever is left after the fixed and percentage slots, by weight.
+    Fill(u32),
+}
+
+impl From<Constraint> for String {
+    fn from(constraint: Constraint) -> String {
+        match constraint {
+            Constraint::Fixed(cells) => cells.to_string(),
+            Constraint::Percent(percent) => format!("{}%", percent),
+            Constraint::Fill(weight) => format!("{}fr", weight),
+        }
+    }
+}
+
+impl TryFrom<String> for Constraint {
+    type Error = String;
+
+    fn try_from(text: String) -> Result<Constraint, String> {
+        let text = text.trim();
+        let parsed = if let Some(percent) = text.strip_suffix('%') {
+            percent.parse().map(Constraint::Percent)
+        } else if let Some(weight) = text.strip_suffix("fr") {
+            weight.parse().map(Constraint::Fill)
+        } else {
+            text.parse().map(Constraint::Fixed)
+        };
+        parsed.map_err(|_| format!("\"{}\" is not a layout constraint", text))
+    }
+}
+
+/// Where an anchored rect is placed inside its area.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Anchor {
+    TopLeft,
+    Top,
+    TopRight,
+    Left,
+    Center,
+    Right,
+    BottomLeft,
+    Bottom,
+    BottomRight,
+}
+
+/// Empty cells kept clear along each edge of an area.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Padding {
+    pub left: u32,
+    pub right: u32,
+    pub top: u32,
+    pub bottom: u32,
+}
+
+impl Padding {
+    /// Create a new Padding.
+    pub fn new(left: u32, right: u32, top: u32, bottom: u32) -> Padding {
+        Padding {
+            left,
+            right,
+            top,
+            bottom,
+        }
+    }
+
+    /// The same padding on every edge.
+    pub fn uniform(cells: u32) -> Padding {
+        Padding::new(cells, cells, cells, cells)
+    }
+
+    /// The part of an area inside this padding. Shrinks to nothing if the padding doesn't fit.
+    pub fn apply(self, area: Rect) -> Rect {
+        let width = area.size.x.saturating_sub(self.left + self.right);
+        let height = area.size.y.saturating_sub(self.top + self.bottom);
+        let left = area.bottom_left.x + self.left.min(area.size.x);
+        let bottom = area.bottom_left.y + self.bottom.min(area.size.y);
+        Rect::new(Point::new(left, bottom), Point::new(width, height))
+    }
+}
+
+/// How the sub-panels of a Panel are arranged.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Arrangement {
+    /// Split the area left to right into slots, one per sub-panel in order, sized by the constraints.
+    Horizontal,
+    /// Split the area top to bottom into slots, one per sub-panel in order, sized by the constraints.
+    Vertical,
+    /// Place every sub-panel at its anchor at the size it wants, one per sub-panel in order.
+    Anchored,
+}
+
+/// Computes the rects of a Panel's sub-panels from the Panel's size, see Panel::apply_layout.
+/// Split layouts use the constraints and spacing, anchored layouts the anchors and sizes.
+// tables go last so this serializes to toml, which needs plain values before tables
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Layout {
+    pub arrangement: Arrangement,
+    #[serde(default)]
+    pub constraints: Vec<Constraint>,
+    #[serde(default)]
+    pub anchors: Vec<Anchor>,
+    #[serde(default)]
+    pub spacing: u32,
+
+    /// The size each anchored sub-panel wants, in order. Taken from the sub-panels when the Layout is first applied,
+    /// so sub-panels shrunk to fit a small area grow back when there is room again.
+    #[serde(default)]
+    pub sizes: Vec<Point>,
+    #[serde(default)]
+    pub padding: Padding,
+}
+
+impl Layout {
+    /// Split the area left to right.
+    pub fn horizontal(constraints: Vec<Constraint>) -> Layout {
+        Layout::split(Direction::Horizontal, constraints)
+    }
+
+    /// Split the area top to bottom.
+    pub fn vertical(constraints: Vec<Constraint>) -> Layout {
+        Layout::split(Direction::Vertical, constraints)
+    }
+
+    /// Split the area in the given direction.
+    pub fn split(direction: Direction, constraints: Vec<Constraint>) -> Layout {
+        Layout {
+            arrangement: match direction {
+                Direction::Horizontal => Arrangement::Horizontal,
+                Direction::Vertical => Arrangement::Vertical,
+            },
+            constraints,
+            anchors: Vec::new(),
+            spacing: 0,
+            sizes: Vec::new(),
+            padding: Padding::default(),
+        }
+    }
+
+    /// Place each sub-panel at an anchor.
+    pub fn anchored(anchors: Vec<Anchor>) -> Layout {
+        Layout {
+            arrangement: Arrangement::Anchored,
+            constraints: Vec::new(),
+            anchors,
+            spacing: 0,
+            sizes: Vec::new(),
+            padding: Padding::default(),
+        }
+    }
+
+    /// Return a new Layout with changed padding.
+    pub fn with_padding(self, padding: Padding) -> Layout {
+        let mut new = self;
+        new.padding = padding;
+        new
+    }
+
+    /// Return a new Layout with changed spacing between split slots.
+    pub fn with_spacing(self, spacing: u32) -> Layout {
+        let mut new = self;
+        new.spacing = spacing;
+        new
+    }
+
+    /// Return a new Layout with the sizes anchored sub-panels want, instead of taking them from the sub-panels.
+    pub fn with_sizes(self, sizes: Vec<Point>) -> Layout {
+        let mut new = self;
+        new.sizes = sizes;
+        new
+    }
+
+    /// The sizes of an anchored layout, first giving every sub-panel without one the size it has now, see Layout::sizes.
+    /// Sizes are matched to sub-panels by their index, so they have to be moved along when the sub-panels are. None for split layouts.
+    pub fn anchored_sizes(&mut self, current: &[Point]) -> Option<&mut Vec<Point>> {
+        if self.arrangement != Arrangement::Anchored {
+            return None;
+        }
+        for size in current.iter().skip(self.sizes.len()) {
+            self.sizes.push(*size);
+        }
+        Some(&mut self.sizes)
+    }
+
+    /// Drop the anchor and size of the sub-panel at index of an anchored layout, when it is taken out of its Panel.
+    pub fn remove_entry(&mut self, index: usize) {
+        if self.arrangement == Arrangement::Anchored {
+            if index < self.anchors.len() {
+                self.anchors.remove(index);
+            }
+            if index < self.sizes.len() {
+                self.sizes.remove(index);
+            }
+        }
+    }
+
+    /// Move the anchor and size of the sub-panel at from of an anchored layout to to, when it is moved among its siblings.
+    pub fn move_entry(&mut self, from: usize, to: usize) {
+        if self.arrangement == Arrangement::Anchored {
+            move_item(&mut self.anchors, from, to);
+            move_item(&mut self.sizes, from, to);
+        }
+    }
+
+    /// Compute a rect inside area for each of the given sizes, in order. Split layouts ignore the sizes except for their count.
+    /// Sizes past the last constraint or anchor get no rect, so there are fewer rects than sizes.
+    pub fn rects(&self, area: Rect, sizes: &[Point]) -> Vec<Rect> {
+        let inner = self.padding.apply(area);
+
+        let direction = match self.arrangement {
+            Arrangement::Horizontal => Direction::Horizontal,
+            Arrangement::Vertical => Direction::Vertical,
+            Arrangement::Anchored => {
+                return sizes
+                    .iter()
+                    .zip(&self.anchors)
+                    .map(|(size, anchor)| anchor_rect(inner, *size, *anchor))
+                    .collect()
+            }
+        };
+
+        let mut rects = split(inner, direction, &self.constraints, self.spacing);
+        rects.truncate(sizes.len());
+        rects
+    }
+}
+
+fn move_item<T>(items: &mut Vec<T>, from: usize, to: usize) {
+    if from < items.len() {
+        let item = items.remove(from);
+        items.insert(to.min(items.len()), item);
+    }
+}
+
+/// Split an area into one rect per constraint. Horizontal splits go left to right, vertical ones top to bottom.
+pub fn split(
+    area: Rect,
+    direction: Direction,
+    constraints: &[Constraint],
+    spacing: u32,
+) -> Vec<Rect> {
+    let length = match direction {
+        Direction::Horizontal => area.size.x,
+        Direction::Vertical => area.size.y,
+    };
+    let gaps = spacing * (constraints.len() as u32).saturating_sub(1);
+    let total = length.saturating_sub(gaps);
+
+    // fixed and percentage slots first, in order, then the fill slots share what's left
+    let mut lengths = Vec::with_capacity(constraints.len());
+    let mut remaining = total;
+    for constraint in constraints {
+        let wanted = match constraint {
+            Constraint::Fixed(cells) => *cells,
+            Constraint::Percent(percent) => total * percent.min(&100) / 100,
+            Constraint::Fill(_) => 0,
+        };
+        let given = wanted.min(remaining);
+        remaining -= given;
+        lengths.push(given);
+    }
+
+    let weights: u32 = constraints
+        .iter()
+        .map(|c| match c {
+            Constraint::Fill(weight) => *weight,
+            _ => 0,
+        })
+        .sum();
+    if weights > 0 {
+        let shared = remaining;
+        let mut last_fill = None;
+        for (i, constraint) in constraints.iter().enumerate() {
+            if let Constraint::Fill(weight) = constraint {
+                let given = shared * weight / weights;
+                lengths[i] = given;
+                remaining -= given;
+                last_fill = Some(i);
+            }
+        }
+        // rounding leftovers go to the last fill slot
+        if let Some(i) = last_fill {
+            lengths[i] += remaining;
+        }
+    }
+
+    let mut rects = Vec::with_capacity(constraints.len());
+    let mut position = 0;
+    for slot in lengths {
+        let rect = match direction {
+            Direction::Horizontal => Rect::new(
+                Point::new(area.bottom_left.x + position, area.bottom_left.y),
+                Point::new(slot, area.size.y),
+            ),
+            Direction::Vertical => {
+                let top = area.size.y - position;
+                Rect::new(
+                    Point::new(area.bottom_left.x, area.bottom_left.y + top - slot),
+                    Point::new(area.size.x, slot),
+                )
+            }
+        };
+        rects.push(rect);
+        // spacing that doesn't fit is dropped, so slots never start outside the area
+        position = (position + slot + spacing).min(length);
+    }
+    rects
+}
+
+/// Place a rect of the given size at an anchor inside area, shrinking it to fit.
+pub fn anchor_rect(area: Rect, size: Point, anchor: Anchor) -> Rect {
+    let size = Point::new(size.x.min(area.size.x), size.y.min(area.size.y));
+    let free_x = area.size.x - size.x;
+    let free_y = area.size.y - size.y;
+
+    let x = match anchor {
+        Anchor::TopLeft | Anchor::Left | Anchor::BottomLeft => 0,
+        Anchor::Top | Anchor::Center | Anchor::Bottom => free_x / 2,
+        Anchor::TopRight | Anchor::Right | Anchor::BottomRight => free_x,
+    };
+    let y = match anchor {
+        Anchor::BottomLeft | Anchor::Bottom | Anchor::BottomRight => 0,
+        Anchor::Left | Anchor::Center | Anchor::Right => free_y / 2,
+        Anchor::TopLeft | Anchor::Top | Anchor::TopRight => free_y,
+    };
+
+    Rect::new(area.bottom_left.plus(Point::new(x, y)), size)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rect(x: u32, y: u32, width: u32, height: u32) -> Rect {
+        Rect::new(Point::new(x, y), Point::new(width, height))
+    }
+
+    #[test]
+    fn split_fixed_percent_and_fill() {
+        let constraints = [
+            Constraint::Fixed(4),
+            Constraint::Percent(25),
+            Constraint::Fill(1),
+            Constraint::Fill(3),
+        ];
+        let rects = split(rect(0, 0, 20, 4), Direction::Horizontal, &constraints, 0);
+
+        // the fill slots share 11 cells 1:3, the rounding leftover goes to the last one
+        assert_eq!(
+            rects,
+            vec![
+                rect(0, 0, 4, 4),
+                rect(4, 0, 5, 4),
+                rect(9, 0, 2, 4),
+                rect(11, 0, 9, 4)
+            ]
+        );
+    }
+
+    #[test]
+    fn split_vertical_goes_top_to_bottom() {
+        let constraints = [Constraint::Fixed(3), Constraint::Fill(1)];
+        let rects = split(rect(2, 1, 6, 10), Direction::Vertical, &constraints, 1);
+
+        assert_eq!(rects, vec![rect(2, 8, 6, 3), rect(2, 1, 6, 6)]);
+    }
+
+    #[test]
+    fn split_gives_what_is_left() {
+        let constraints = [
+            Constraint::Fixed(8),
+            Constraint::Percent(150),
+            Constraint::Fixed(8),
+        ];
+        let rects = split(rect(0, 0, 10, 2), Direction::Horizontal, &constraints, 0);
+
+        assert_eq!(
+            rects,
+            vec![rect(0, 0, 8, 2), rect(8, 0, 2, 2), rect(10, 0, 0, 2)]
+        );
+    }
+
+    #[test]
+    fn split_with_spacing() {
+        let constraints = [Constraint::Fill(1), Constraint::Fill(1)];
+        let rects = split(rect(0, 0, 10, 3), Direction::Horizontal, &constraints, 2);
+
+        assert_eq!(rects, vec![rect(0, 0, 4, 3), rect(6, 0, 4, 3)]);
+    }
+
+    #[test]
+    fn padding() {
+        let area = rect(1, 1, 10, 10);
+        assert_eq!(Padding::new(1, 2, 3, 0).apply(area), rect(2, 1, 7, 7));
+        assert_eq!(Padding::uniform(6).apply(area).size, Point::new(0, 0));
+
+        let layout =
+            Layout::horizontal(vec![Constraint::Fill(1)]).with_padding(Padding::uniform(1));
+        assert_eq!(
+            layout.rects(area, &[Point::new(1, 1)]),
+            vec![rect(2, 2, 8, 8)]
+        );
+    }
+
+    #[test]
+    fn extra_sizes_get_no_rect() {
+        let sizes = [Point::new(2, 2); 3];
+        let area = rect(0, 0, 10, 10);
+
+        let split = Layout::vertical(vec![Constraint::Fill(1), Constraint::Fill(1)]);
+        assert_eq!(split.rects(area, &sizes).len(), 2);
+        let anchored = Layout::anchored(vec![Anchor::Center]);
+        assert_eq!(anchored.rects(area, &sizes), vec![rect(4, 4, 2, 2)]);
+    }
+
+    #[test]
+    fn anchors() {
+        let area = rect(2, 1, 10, 6);
+        let size = Point::new(4, 2);
+
+        assert_eq!(anchor_rect(area, size, Anchor::TopLeft), rect(2, 5, 4, 2));
+        assert_eq!(anchor_rect(area, size, Anchor::Top), rect(5, 5, 4, 2));
+        assert_eq!(anchor_rect(area, size, Anchor::Center), rect(5, 3, 4, 2));
+        assert_eq!(anchor_rect(area, size, Anchor::Right), rect(8, 3, 4, 2));
+        assert_eq!(
+            anchor_rect(area, size, Anchor::BottomRight),
+            rect(8, 1, 4, 2)
+        );
+        assert_eq!(
+            anchor_rect(area, size, Anchor::BottomLeft),
+            rect(2, 1, 4, 2)
+        );
+    }
+
+    #[test]
+    fn anchored_rects_shrink_to_fit() {
+        let area = rect(2, 1, 10, 6);
+
+        assert_eq!(
+            anchor_rect(area, Point::new(20, 3), Anchor::Bottom),
+            rect(2, 1, 10, 3)
+        );
+        assert_eq!(anchor_rect(area, Point::new(20, 20), Anchor::Center), area);
+    }
+}
@@ -1,3 +1,7 @@
 mod geom;
+mod layout;
+mod shapes;
 
 pub use geom::*;
+pub use layout::*;
+pub use shapes::*;
@@ -0,0 +1,211 @@
+use std::collections::HashSet;
+
+use crate::geometry::{Point, Rect};
+
+/// The Points of a line from one Point to another, both included, using Bresenham's algorithm.
+pub fn line(from: Point, to: Point) -> Vec<Point> {
+    let (mut x, mut y) = (i64::from(from.x), i64::from(from.y));
+    let (end_x, end_y) = (i64::from(to.x), i64::from(to.y));
+    let dx = (end_x - x).abs();
+    let dy = -(end_y - y).abs();
+    let step_x = if x < end_x { 1 } else { -1 };
+    let step_y = if y < end_y { 1 } else { -1 };
+    let mut error = dx + dy;
+
+    let mut result = Vec::with_capacity((dx - dy + 1) as usize);
+    loop {
+        result.push(Point::new(x as u32, y as u32));
+        if x == end_x && y == end_y {
+            break;
+        }
+        let doubled = 2 * error;
+        if doubled >= dy {
+            error += dy;
+            x += step_x;
+        }
+        if doubled <= dx {
+            error += dx;
+            y += step_y;
+        }
+    }
+    result
+}
+
+/// The Points on the outer edge of a Rect.
+pub fn rect_outline(rect: Rect) -> Vec<Point> {
+    let (left, bottom) = (rect.bottom_left.x, rect.bottom_left.y);
+    let (right, top) = (left + rect.size.x, bottom + rect.size.y);
+    rect.points()
+        .into_iter()
+        .filter(|p| p.x == left || p.x == right - 1 || p.y == bottom || p.y == top - 1)
+        .collect()
+}
+
+/// The Points of an ellipse around center with the given horizontal and vertical radius, either filled or only its outline.
+/// Points that would fall below zero are left out.
+pub fn ellipse(center: Point, radius_x: u32, radius_y: u32, filled: bool) -> Vec<Point> {
+    let (rx, ry) = (i64::from(radius_x), i64::from(radius_y));
+    // half a cell extra keeps small ellipses round instead of diamond shaped
+    let inside = |dx: i64, dy: i64| {
+        let fx = dx as f64 / (rx as f64 + 0.5);
+        let fy = dy as f64 / (ry as f64 + 0.5);
+        fx * fx + fy * fy <= 1.0
+    };
+
+    let mut offsets = HashSet::new();
+    for dy in -ry..=ry {
+        for dx in -rx..=rx {
+            if inside(dx, dy) {
+                offsets.insert((dx, dy));
+            }
+        }
+    }
+    if !filled {
+        let all = offsets.clone();
+        offsets.retain(|(dx, dy)| {
+            !(all.contains(&(dx - 1, *dy))
+                && all.contains(&(dx + 1, *dy))
+                && all.contains(&(*dx, dy - 1))
+                && all.contains(&(*dx, dy + 1)))
+        });
+    }
+
+    let (cx, cy) = (i64::from(center.x), i64::from(center.y));
+    let mut result: Vec<Point> = offsets
+        .into_iter()
+        .map(|(dx, dy)| (cx + dx, cy + dy))
+        .filter(|(x, y)| *x >= 0 && *y >= 0)
+        .map(|(x, y)| Point::new(x as u32, y as u32))
+        .collect();
+    result.sort_by_key(|p| (p.x, p.y));
+    result
+}
+
+/// The Points of a circle around center, either filled or only its outline.
+pub fn circle(center: Point, radius: u32, filled: bool) -> Vec<Point> {
+    ellipse(center, radius, radius, filled)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn points(coords: &[(u32, u32)]) -> Vec<Point> {
+        coords.iter().map(|&(x, y)| Point::new(x, y)).collect()
+    }
+
+    #[test]
+    fn lines_include_both_endpoints() {
+        let (from, to) = (Point::new(1, 2), Point::new(6, 4));
+        let forward = line(from, to);
+        assert_eq!(forward.first(), Some(&from));
+        assert_eq!(forward.last(), Some(&to));
+        assert_eq!(forward.len(), 6);
+
+        let backward = line(to, from);
+        assert_eq!(backward.first(), Some(&to));
+        assert_eq!(backward.last(), Some(&from));
+        assert_eq!(backward.len(), 6);
+    }
+
+    #[test]
+    fn lines_have_no_gaps() {
+        for &(from, to) in &[((0, 0), (3, 3)), ((2, 9), (4, 0)), ((7, 1), (0, 2))] {
+            let points = line(Point::new(from.0, from.1), Point::new(to.0, to.1));
+            for pair in points.windows(2) {
+                let dx = (i64::from(pair[1].x) - i64::from(pair[0].x)).abs();
+                let dy = (i64::from(pair[1].y) - i64::from(pair[0].y)).abs();
+                assert!(dx <= 1 && dy <= 1 && dx + dy > 0);
+            }
+        }
+        assert_eq!(
+            line(Point::new(0, 0), Point::new(3, 3)),
+            points(&[(0, 0), (1, 1), (2, 2), (3, 3)])
+        );
+    }
+
+    #[test]
+    fn single_point_lines() {
+        let point = Point::new(4, 4);
+        assert_eq!(line(point, point), vec![point]);
+    }
+
+    #[test]
+    fn rect_outlines() {
+        let outline = rect_outline(Rect::new(Point::new(1, 1), Point::new(4, 3)));
+        assert_eq!(outline.len(), 10);
+        assert!(!outline.contains(&Point::new(2, 2)));
+        assert!(outline.contains(&Point::new(1, 1)));
+        assert!(outline.contains(&Point::new(4, 3)));
+
+        assert_eq!(
+            rect_outline(Rect::new(Point::new(2, 2), Point::new(1, 1))),
+            points(&[(2, 2)])
+        );
+        assert_eq!(
+            rect_outline(Rect::new(Point::new(0, 0), Point::new(1, 3))).len(),
+            3
+        );
+        assert!(rect_outline(Rect::new(Point::new(0, 0), Point::new(0, 3))).is_empty());
+        assert!(rect_outline(Rect::new(Point::new(5, 5), Point::new(3, 0))).is_empty());
+    }
+
+    #[test]
+    fn small_circles() {
+        let center = Point::new(5, 5);
+        assert_eq!(circle(center, 0, true), vec![center]);
+        assert_eq!(circle(center, 0, false), vec![center]);
+
+        // radius 1 covers the whole 3x3 square, its outline everything but the center
+        assert_eq!(circle(center, 1, true).len(), 9);
+        let outline = circle(center, 1, false);
+        assert_eq!(outline.len(), 8);
+        assert!(!outline.contains(&center));
+    }
+
+    #[test]
+    fn circles_are_round() {
+        let center = Point::new(5, 5);
+        let filled = circle(center, 2, true);
+        for &(x, y) in &[(3, 5), (7, 5), (5, 3), (5, 7)] {
+            assert!(filled.contains(&Point::new(x, y)));
+        }
+        for &(x, y) in &[(3, 3), (7, 7), (3, 7), (7, 3)] {
+            assert!(!filled.contains(&Point::new(x, y)));
+        }
+
+        let outline = circle(center, 2, false);
+        assert!(outline.iter().all(|p| filled.contains(p)));
+        assert!(!outline.contains(&center));
+        assert!(outline.contains(&Point::new(7, 5)));
+    }
+
+    #[test]
+    fn ellipses() {
+        let center = Point::new(5, 5);
+        let wide = ellipse(center, 3, 1, true);
+        assert!(wide.contains(&Point::new(2, 5)));
+        assert!(wide.contains(&Point::new(8, 5)));
+        assert!(wide.contains(&Point::new(5, 6)));
+        assert!(!wide.contains(&Point::new(5, 7)));
+        assert!(wide
+            .iter()
+            .all(|p| p.x >= 2 && p.x <= 8 && p.y >= 4 && p.y <= 6));
+
+        // a flat ellipse is a line
+        assert_eq!(ellipse(center, 2, 0, true).len(), 5);
+    }
+
+    #[test]
+    fn points_below_zero_are_left_out() {
+        let cut = circle(Point::new(0, 1), 2, true);
+        let whole = circle(Point::new(2, 3), 2, true);
+
+        let expected: Vec<Point> = whole
+            .into_iter()
+            .filter(|p| p.x >= 2 && p.y >= 2)
+            .map(|p| Point::new(p.x - 2, p.y - 2))
+            .collect();
+        assert_eq!(cut, expected);
+    }
+}
@@ -0,0 +1,268 @@
+use glium::texture::RawImage2d;
+use glium::Display;
+use image;
+use image::{Pixel, Rgba, RgbaImage};
+use std::fs::{create_dir_all, File};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::error::{OozeError, OozeResult};
+use crate::geometry::Rect;
+use crate::graphics::SpriteMap;
+use crate::terminal::{Drawable, FloatingSprite, Terminal};
+
+/// Where a captured frame comes from.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CaptureSource {
+    /// Read back what was last shown in the window, including post-processing.
+    FrontBuffer,
+    /// Render the Terminal on the CPU at its native glyph size, independent of the window.
+    Terminal,
+}
+
+/// The file format of a recording.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RecordFormat {
+    /// A single animated gif file.
+    Gif,
+    /// A folder of numbered png files, frame_00000.png, frame_00001.png, ...
+    PngSequence,
+}
+
+/// Read the window's front buffer, the frame shown last, into an image, top row first.
+pub fn capture_front_buffer(display: &Display) -> OozeResult<RgbaImage> {
+    let raw: RawImage2d<u8> = display.read_front_buffer();
+    let buffer =
+        RgbaImage::from_raw(raw.width, raw.height, raw.data.into_owned()).ok_or_else(|| {
+            OozeError::BadFormatError("front buffer size doesn't match its dimensions".to_string())
+        })?;
+    // OpenGL's first row is the bottom of the screen
+    Ok(image::imageops::flip_vertical(&buffer))
+}
+
+/// Render a Terminal on the CPU the same way the default shaders draw it: transparent texels show the background color, all others the foreground color times the texel.
+/// The image is term_size * glyph_size pixels, sprites of another size are scaled to the glyph size.
+pub fn render_terminal(terminal: &Terminal, sprites: &SpriteMap) -> OozeResult<RgbaImage> {
+    let dims = terminal.dims;
+    let (glyph_width, glyph_height) = (dims.glyph_size.x, dims.glyph_size.y);
+    let mut frame = RgbaImage::from_pixel(
+        dims.term_size.x * glyph_width,
+        dims.term_size.y * glyph_height,
+        Rgba([0, 0, 0, 255]),
+    );
+    let screen = Rect::of_size(dims.term_size);
+    let (frame_width, frame_height) = (i64::from(frame.width()), i64::from(frame.height()));
+
+    for drawable in terminal.drawables() {
+        let (glyph, point) = match drawable {
+            Drawable::Glyph(glyph, point) => (glyph, point),
+            Drawable::Sprite(sprite) => {
+                render_floating_sprite(&mut frame, sprite, sprites, terminal)?;
+                continue;
+            }
+        };
+        if !screen.contains_point(point) {
+            continue;
+        }
+        let sprite = &sprites.get(&glyph.sprite_id)?.image;
+        let (fg_color, bg_color) = (glyph.drawn_fg_color(), glyph.drawn_bg_color());
+        let shift = glyph.effects.offset;
+        let size = glyph.transform.size();
+        let (width, height) = (glyph_width * size.x, glyph_height * size.y);
+        let left =
+            i64::from(point.x * glyph_width) + (shift[0] * glyph_width as f32).round() as i64;
+        // terminal rows count up from the bottom, image rows down from the top
+        let top = (i64::from(dims.term_size.y) - i64::from(point.y) - i64::from(size.y))
+            * i64::from(glyph_height)
+            - (shift[1] * glyph_height as f32).round() as i64;
+
+        for py in 0..height {
+            for px in 0..width {
+                let (x, y) = (left + i64::from(px), top + i64::from(py));
+                if x < 0 || y < 0 || x >= frame_width || y >= frame_height {
+                    continue;
+                }
+                let [u, v] = glyph.transform.source_coords([
+                    (px as f32 + 0.5) / width as f32,
+                    1.0 - (py as f32 + 0.5) / height as f32,
+                ]);
+                let texel = sprite.get_pixel(
+                    ((u * sprite.width() as f32) as u32).min(sprite.width() - 1),
+                    (((1.0 - v) * sprite.height() as f32) as u32).min(sprite.height() - 1),
+                );
+                let color = shade(texel, fg_color, bg_color);
+                blend_pixel(frame.get_pixel_mut(x as u32, y as u32), color);
+            }
+        }
+    }
+
+    Ok(frame)
+}
+
+/// Draw a FloatingSprite onto a frame rendered by render_terminal, sampling the sprite at the center of each pixel it covers.
+fn render_floating_sprite(
+    frame: &mut RgbaImage,
+    sprite: &FloatingSprite,
+    sprites: &SpriteMap,
+    terminal: &Terminal,
+) -> OozeResult<()> {
+    let image = &sprites.get(&sprite.sprite_id)?.image;
+    let glyph_size = terminal.dims.glyph_size;
+    let (glyph_width, glyph_height) = (glyph_size.x as f32, glyph_size.y as f32);
+    let term_height = terminal.dims.term_size.y as f32;
+
+    // the pixels around the rotated quad
+    let corners = sprite.corners();
+    let xs = corners.iter().map(|corner| corner[0] * glyph_width);
+    let ys = corners
+        .iter()
+        .map(|corner| (term_height - corner[1]) * glyph_height);
+    let left = xs.clone().fold(f32::MAX, f32::min).floor().max(0.0) as u32;
+    let right = xs.fold(f32::MIN, f32::max).ceil().max(0.0) as u32;
+    let top = ys.clone().fold(f32::MAX, f32::min).floor().max(0.0) as u32;
+    let bottom = ys.fold(f32::MIN, f32::max).ceil().max(0.0) as u32;
+
+    for y in top..bottom.min(frame.height()) {
+        for x in left..right.min(frame.width()) {
+            let cell = [
+                (x as f32 + 0.5) / glyph_width,
+                term_height - (y as f32 + 0.5) / glyph_height,
+            ];
+            let [u, v] = sprite.sprite_coords(cell);
+            if !(0.0..1.0).contains(&u) || !(0.0..1.0).contains(&v) {
+                continue;
+            }
+            // image rows count down from the top of the sprite
+            let texel = image.get_pixel(
+                ((u * image.width() as f32) as u32).min(image.width() - 1),
+                (((1.0 - v) * image.height() as f32) as u32).min(image.height() - 1),
+            );
+            let color = shade(texel, sprite.tint, sprite.bg_color);
+            blend_pixel(frame.get_pixel_mut(x, y), color);
+        }
+    }
+
+    Ok(())
+}
+
+/// The color of a texel the way the default shaders draw it.
+fn shade(texel: &Rgba<u8>, fg_color: [f32; 4], bg_color: [f32; 4]) -> [f32; 4] {
+    let texel = texel.channels();
+    if texel[3] == 0 {
+        bg_color
+    } else {
+        let mut color = fg_color;
+        for (c, t) in color.iter_mut().zip(texel) {
+            *c *= f32::from(*t) / 255.0;
+        }
+        color
+    }
+}
+
+/// Alpha blend a float color over a pixel.
+fn blend_pixel(pixel: &mut Rgba<u8>, color: [f32; 4]) {
+    let alpha = color[3];
+    let channels = pixel.channels_mut();
+    for i in 0..3 {
+        let below = f32::from(channels[i]) / 255.0;
+        channels[i] = ((color[i] * alpha + below * (1.0 - alpha)) * 255.0).round() as u8;
+    }
+}
+
+/// Save an image as a png file.
+pub fn save_png(image: &RgbaImage, path: &Path) -> OozeResult<()> {
+    image.save(path)?;
+    Ok(())
+}
+
+/// Records consecutive frames into an animated gif or a png sequence.
+pub struct Recorder {
+    pub format: RecordFormat,
+    pub source: CaptureSource,
+    pub path: PathBuf,
+    pub frame_delay: Duration,
+
+    frame_count: u32,
+    /// The gif file, until the first frame gives the gif its size and it is handed to the encoder.
+    gif_file: Option<File>,
+    gif_encoder: Option<gif::Encoder<BufWriter<File>>>,
+}
+
+impl Recorder {
+    /// Start a recording at the given path: a gif file, or a folder for a png sequence which is created if needed.
+    /// frame_delay is how long each frame is shown when the gif is played back, which loops forever.
+    pub fn new(
+        path: &Path,
+        format: RecordFormat,
+        source: CaptureSource,
+        frame_delay: Duration,
+    ) -> OozeResult<Recorder> {
+        let gif_file = match format {
+            RecordFormat::Gif => Some(File::create(path)?),
+            RecordFormat::PngSequence => {
+                create_dir_all(path)?;
+                None
+            }
+        };
+
+        let recorder = Recorder {
+            format,
+            source,
+            path: path.to_path_buf(),
+            frame_delay,
+            frame_count: 0,
+            gif_file,
+            gif_encoder: None,
+        };
+
+        Ok(recorder)
+    }
+
+    /// The number of frames recorded so far.
+    pub fn frame_count(&self) -> u32 {
+        self.frame_count
+    }
+
+    /// Add a frame to the recording.
+    pub fn add_frame(&mut self, frame: RgbaImage) -> OozeResult<()> {
+        match self.format {
+            RecordFormat::Gif => {
+                let (width, height) = frame.dimensions();
+                if width > u32::from(u16::MAX) || height > u32::from(u16::MAX) {
+                    return Err(Box::new(OozeError::OutOfBoundsError));
+                }
+                if let Some(file) = self.gif_file.take() {
+                    let mut encoder =
+                        gif::Encoder::new(BufWriter::new(file), width as u16, height as u16, &[])?;
+                    encoder.set_repeat(gif::Repeat::Infinite)?;
+                    self.gif_encoder = Some(encoder);
+                }
+                let mut pixels = frame.into_raw();
+                let mut gif_frame = gif::Frame::from_rgba(width as u16, height as u16, &mut pixels);
+                // gif delays are in hundredths of a second
+                gif_frame.delay = (self.frame_delay.as_millis() / 10) as u16;
+                self.gif_encoder
+                    .as_mut()
+                    .ok_or(OozeError::GenericError)?
+                    .write_frame(&gif_frame)?;
+            }
+            RecordFormat::PngSequence => {
+                let file = self.path.join(format!("frame_{:05}.png", self.frame_count));
+                save_png(&frame, &file)?;
+            }
+        }
+        self.frame_count += 1;
+
+        Ok(())
+    }
+
+    /// Finish the recording, writing the end of the gif file. A gif without frames is left empty.
+    pub fn finish(mut self) -> OozeResult<()> {
+        if let Some(encoder) = self.gif_encoder.take() {
+            encoder.into_inner()?.flush()?;
+        }
+
+        Ok(())
+    }
+}
@@ -1,5 +1,9 @@
-mod render;
-mod sprite;
-
-pub use render::*;
-pub use sprite::*;
+mod capture;
+mod postprocess;
+mod render;
+mod sprite;
+
+pub use capture::*;
+pub use postprocess::*;
+pub use render::*;
+pub use sprite::*;
@@ -0,0 +1,316 @@
+use glium;
+use glium::framebuffer::SimpleFrameBuffer;
+use glium::texture::Texture2d;
+use glium::uniforms::{
+    MagnifySamplerFilter, MinifySamplerFilter, SamplerBehavior, UniformValue, Uniforms,
+};
+use glium::{Display, Frame, Program, Surface, VertexBuffer};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::error::OozeResult;
+use crate::graphics::{get_shader, Vertex, DEFAULT_VERTEX_SHADER};
+
+/// Source of the CRT scanline post-process shader.
+pub const SCANLINES_SHADER: &str =
+    include_str!("../../resources/shaders/postprocess/scanlines.frag");
+
+/// Source of the vignette post-process shader.
+pub const VIGNETTE_SHADER: &str = include_str!("../../resources/shaders/postprocess/vignette.frag");
+
+/// Source of the bloom post-process shader.
+pub const BLOOM_SHADER: &str = include_str!("../../resources/shaders/postprocess/bloom.frag");
+
+/// Source of the color grading post-process shader.
+pub const COLOR_GRADE_SHADER: &str =
+    include_str!("../../resources/shaders/postprocess/color_grade.frag");
+
+/// A value that can be handed to a shader as a uniform.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum UniformData {
+    Float(f32),
+    Int(i32),
+    Vec2([f32; 2]),
+    Vec3([f32; 3]),
+    Vec4([f32; 4]),
+}
+
+impl UniformData {
+    fn as_uniform_value<'a>(self) -> UniformValue<'a> {
+        match self {
+            UniformData::Float(v) => UniformValue::Float(v),
+            UniformData::Int(v) => UniformValue::SignedInt(v),
+            UniformData::Vec2(v) => UniformValue::Vec2(v),
+            UniformData::Vec3(v) => UniformValue::Vec3(v),
+            UniformData::Vec4(v) => UniformValue::Vec4(v),
+        }
+    }
+}
+
+/// A set of named uniforms defined by the user. Uniforms the shader doesn't declare are ignored.
+#[derive(Clone, Debug, Default)]
+pub struct UniformSet {
+    values: HashMap<String, UniformData>,
+}
+
+impl UniformSet {
+    /// Create an empty UniformSet.
+    pub fn new() -> UniformSet {
+        UniformSet {
+            values: HashMap::new(),
+        }
+    }
+
+    /// Set the uniform with the given name, replacing any previous value.
+    pub fn set(&mut self, name: &str, value: UniformData) {
+        self.values.insert(name.to_string(), value);
+    }
+
+    /// Get the value of the uniform with the given name.
+    pub fn get(&self, name: &str) -> Option<UniformData> {
+        self.values.get(name).copied()
+    }
+
+    /// Remove the uniform with the given name.
+    pub fn remove(&mut self, name: &str) -> Option<UniformData> {
+        self.values.remove(name)
+    }
+}
+
+impl Uniforms for UniformSet {
+    fn visit_values<'a, F: FnMut(&str, UniformValue<'a>)>(&'a self, mut visit: F) {
+        for (name, value) in &self.values {
+            visit(name, value.as_uniform_value());
+        }
+    }
+}
+
+/// The uniforms for one draw call: a texture sampled with nearest filtering, plus a UniformSet.
+pub struct TexturedUniforms<'a> {
+    pub texture: &'a Texture2d,
+    pub values: &'a [(&'static str, UniformData)],
+    pub user: &'a UniformSet,
+}
+
+impl<'b> Uniforms for TexturedUniforms<'b> {
+    fn visit_values<'a, F: FnMut(&str, UniformValue<'a>)>(&'a self, mut visit: F) {
+        let sampler = SamplerBehavior {
+            magnify_filter: MagnifySamplerFilter::Nearest,
+            minify_filter: MinifySamplerFilter::Nearest,
+            ..Default::default()
+        };
+        visit("tex", UniformValue::Texture2d(self.texture, Some(sampler)));
+        for (name, value) in self.values {
+            visit(name, value.as_uniform_value());
+        }
+        self.user.visit_values(visit);
+    }
+}
+
+/// One full-screen pass of the post-processing chain.
+/// The fragment shader receives the previous pass as `tex`, along with `time`, `screen_size` and the pass's own uniforms.
+pub struct PostProcessPass {
+    pub program: Program,
+    pub uniforms: UniformSet,
+    pub enabled: bool,
+
+    pub fragment_path: Option<PathBuf>,
+}
+
+impl PostProcessPass {
+    /// Create a new pass from fragment shader source, using the default vertex shader.
+    pub fn new(display: &Display, fragment_shader: &str) -> OozeResult<PostProcessPass> {
+        PostProcessPass::with_shaders(display, DEFAULT_VERTEX_SHADER, fragment_shader)
+    }
+
+    /// Create a new pass from vertex and fragment shader source.
+    pub fn with_shaders(
+        display: &Display,
+        vertex_shader: &str,
+        fragment_shader: &str,
+    ) -> OozeResult<PostProcessPass> {
+        let pass = PostProcessPass {
+            program: Program::from_source(display, vertex_shader, fragment_shader, None)?,
+            uniforms: UniformSet::new(),
+            enabled: true,
+            fragment_path: None,
+        };
+
+        Ok(pass)
+    }
+
+    /// Create a new pass from a fragment shader file, using the default vertex shader.
+    /// The path is remembered so the pass can be reloaded.
+    pub fn from_file(display: &Display, fragment_path: &Path) -> OozeResult<PostProcessPass> {
+        let mut pass = PostProcessPass::new(display, &get_shader(fragment_path)?)?;
+        pass.fragment_path = Some(fragment_path.to_path_buf());
+        Ok(pass)
+    }
+
+    /// Compile this pass's fragment shader file again, if it was loaded from one.
+    /// Returns the new program without replacing the current one.
+    pub fn reload_program(&self, display: &Display) -> OozeResult<Option<Program>> {
+        match &self.fragment_path {
+            Some(path) => {
+                let program =
+                    Program::from_source(display, DEFAULT_VERTEX_SHADER, &get_shader(path)?, None)?;
+                Ok(Some(program))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// CRT style scanlines that darken every other row of pixels.
+    pub fn scanlines(display: &Display, intensity: f32) -> OozeResult<PostProcessPass> {
+        let mut pass = PostProcessPass::new(display, SCANLINES_SHADER)?;
+        pass.set_uniform("intensity", UniformData::Float(intensity));
+        Ok(pass)
+    }
+
+    /// Darken the edges of the screen, starting at radius from the center (in screen fractions).
+    pub fn vignette(display: &Display, radius: f32, softness: f32) -> OozeResult<PostProcessPass> {
+        let mut pass = PostProcessPass::new(display, VIGNETTE_SHADER)?;
+        pass.set_uniform("radius", UniformData::Float(radius));
+        pass.set_uniform("softness", UniformData::Float(softness));
+        Ok(pass)
+    }
+
+    /// Make colors brighter than the threshold glow onto their surroundings.
+    pub fn bloom(display: &Display, threshold: f32, intensity: f32) -> OozeResult<PostProcessPass> {
+        let mut pass = PostProcessPass::new(display, BLOOM_SHADER)?;
+        pass.set_uniform("threshold", UniformData::Float(threshold));
+        pass.set_uniform("intensity", UniformData::Float(intensity));
+        Ok(pass)
+    }
+
+    /// Adjust brightness, contrast and saturation, then multiply by a tint.
+    pub fn color_grade(
+        display: &Display,
+        brightness: f32,
+        contrast: f32,
+        saturation: f32,
+        tint: [f32; 3],
+    ) -> OozeResult<PostProcessPass> {
+        let mut pass = PostProcessPass::new(display, COLOR_GRADE_SHADER)?;
+        pass.set_uniform("brightness", UniformData::Float(brightness));
+        pass.set_uniform("contrast", UniformData::Float(contrast));
+        pass.set_uniform("saturation", UniformData::Float(saturation));
+        pass.set_uniform("tint", UniformData::Vec3(tint));
+        Ok(pass)
+    }
+
+    /// Set one of this pass's uniforms.
+    pub fn set_uniform(&mut self, name: &str, value: UniformData) {
+        self.uniforms.set(name, value);
+    }
+}
+
+/// A chain of post-process passes. The scene is drawn to an offscreen texture which is run through each enabled pass in order, the last one drawing to the window.
+pub struct PostProcessChain {
+    pub passes: Vec<PostProcessPass>,
+
+    targets: Vec<Texture2d>,
+    quad: Option<VertexBuffer<Vertex>>,
+}
+
+impl PostProcessChain {
+    /// Create an empty chain.
+    pub fn new() -> PostProcessChain {
+        PostProcessChain {
+            passes: Vec::new(),
+            targets: Vec::new(),
+            quad: None,
+        }
+    }
+
+    /// Add a pass to the end of the chain.
+    pub fn push(&mut self, pass: PostProcessPass) {
+        self.passes.push(pass);
+    }
+
+    /// Check if any pass in the chain is enabled.
+    pub fn is_active(&self) -> bool {
+        self.passes.iter().any(|pass| pass.enabled)
+    }
+
+    /// Draw the scene with draw_scene into an offscreen texture, then run it through every enabled pass onto the frame.
+    pub fn render<F>(
+        &mut self,
+        display: &Display,
+        frame: &mut Frame,
+        time: f32,
+        draw_scene: F,
+    ) -> OozeResult<()>
+    where
+        F: FnOnce(&mut SimpleFrameBuffer) -> OozeResult<()>,
+    {
+        let (width, height) = display.get_framebuffer_dimensions();
+        self.prepare(display, width, height)?;
+
+        {
+            let mut buffer = SimpleFrameBuffer::new(display, &self.targets[0])?;
+            buffer.clear_color(0.0, 0.0, 0.0, 1.0);
+            draw_scene(&mut buffer)?;
+        }
+
+        let quad = self.quad.as_ref().unwrap();
+        let indices = glium::index::NoIndices(glium::index::PrimitiveType::TriangleStrip);
+        let values = [
+            ("time", UniformData::Float(time)),
+            (
+                "screen_size",
+                UniformData::Vec2([width as f32, height as f32]),
+            ),
+        ];
+
+        let enabled: Vec<&PostProcessPass> =
+            self.passes.iter().filter(|pass| pass.enabled).collect();
+        for (i, pass) in enabled.iter().enumerate() {
+            let uniforms = TexturedUniforms {
+                texture: &self.targets[i % 2],
+                values: &values,
+                user: &pass.uniforms,
+            };
+
+            if i == enabled.len() - 1 {
+                frame.draw(quad, indices, &pass.program, &uniforms, &Default::default())?;
+            } else {
+                let mut buffer = SimpleFrameBuffer::new(display, &self.targets[(i + 1) % 2])?;
+                buffer.draw(quad, indices, &pass.program, &uniforms, &Default::default())?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// (Re)create the offscreen textures if the window size changed, and the full-screen quad.
+    fn prepare(&mut self, display: &Display, width: u32, height: u32) -> OozeResult<()> {
+        let resized = self.targets.first().map_or(true, |t| {
+            t.get_width() != width || t.get_height() != Some(height)
+        });
+        if resized {
+            self.targets = vec![
+                Texture2d::empty(display, width, height)?,
+                Texture2d::empty(display, width, height)?,
+            ];
+        }
+
+        if self.quad.is_none() {
+            let verts = [
+                Vertex::from_arrays([-1.0, 1.0], [0.0, 1.0]),
+                Vertex::from_arrays([1.0, 1.0], [1.0, 1.0]),
+                Vertex::from_arrays([-1.0, -1.0], [0.0, 0.0]),
+                Vertex::from_arrays([1.0, -1.0], [1.0, 0.0]),
+            ];
+            self.quad = Some(VertexBuffer::new(display, &verts)?);
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for PostProcessChain {
+    fn default() -> PostProcessChain {
+        PostProcessChain::new()
+    }
+}
@@ -4,6 +4,14 @@ use std::path::Path;
 
 use crate::error::OozeResult;
 
+/// Source of the default vertex shader, compiled into the binary.
+pub const DEFAULT_VERTEX_SHADER: &str =
+    include_str!("../../resources/shaders/vertex/v_shader_default.vert");
+
+/// Source of the default fragment shader, compiled into the binary.
+pub const DEFAULT_FRAGMENT_SHADER: &str =
+    include_str!("../../resources/shaders/fragment/f_shader_default.frag");
+
 /// A vertex for glium's rendering program.
 #[derive(Copy, Clone)]
 pub struct Vertex {
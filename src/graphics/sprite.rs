@@ -3,35 +3,30 @@ use glium::texture::Texture2d;
 use glium::Display;
 use glob::glob;
 use image;
+use image::{FilterType, RgbaImage};
 use rand::seq::IteratorRandom;
 use rand::thread_rng;
 use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::ffi::OsStr;
 use std::fs::{read_to_string, File};
 use std::io::BufReader;
-use std::path::Path;
-use std::str::FromStr;
+use std::path::{Path, PathBuf};
 use toml::Value;
 
 use crate::error::{OozeError, OozeResult};
+use crate::geometry::Point;
 
-/// A sprite that can be drawn to the window. Currently only contains a texture.
+/// A sprite that can be drawn to the window. Contains its texture and a copy of the image for drawing on the CPU.
 pub struct Sprite {
     pub texture: Texture2d,
+    pub image: RgbaImage,
 }
 
 impl Sprite {
     /// Create a new Sprite from a png image and a display context.
     pub fn new<P: AsRef<Path>>(img_path: P, display: &Display) -> OozeResult<Sprite> {
-        let r = BufReader::new(File::open(img_path)?);
-        let image = image::load(r, image::PNG)?.to_rgba();
-        let image_dimensions = image.dimensions();
-        let image = RawImage2d::from_raw_rgba_reversed(&image.into_raw(), image_dimensions);
-
-        let sprite = Sprite {
-            texture: Texture2d::new(display, image)?,
-        };
-
-        Ok(sprite)
+        Sprite::from_image(load_png(img_path)?, display)
     }
 
     /// Create a new Sprite from a png spritesheet and image coordinates.
@@ -43,45 +38,156 @@ impl Sprite {
         width: u32,
         height: u32,
     ) -> OozeResult<Sprite> {
-        let r = BufReader::new(File::open(sheet_path)?);
-        let mut sheet = image::load(r, image::PNG)?.to_rgba();
+        let mut sheet = load_png(sheet_path)?;
         let sub_image = image::imageops::crop(&mut sheet, x, y, width, height).to_image();
-        let image_dimensions = sub_image.dimensions();
-        let sub_image = RawImage2d::from_raw_rgba_reversed(&sub_image.into_raw(), image_dimensions);
+
+        Sprite::from_image(sub_image, display)
+    }
+
+    /// Create a new Sprite from an already loaded image.
+    pub fn from_image(image: RgbaImage, display: &Display) -> OozeResult<Sprite> {
+        let image_dimensions = image.dimensions();
+        let raw = RawImage2d::from_raw_rgba_reversed(&image, image_dimensions);
 
         let sprite = Sprite {
-            texture: Texture2d::new(display, sub_image)?,
+            texture: Texture2d::new(display, raw)?,
+            image,
         };
 
         Ok(sprite)
     }
 }
 
+/// The built-in CP437 font sheet, compiled into the binary.
+#[cfg(feature = "default-font")]
+pub const DEFAULT_FONT_SHEET: &[u8] = include_bytes!("../../resources/sheets/default_font.png");
+
+/// The metadata of the built-in CP437 font sheet, compiled into the binary.
+#[cfg(feature = "default-font")]
+pub const DEFAULT_FONT_METADATA: &str = include_str!("../../resources/sheets/default_font.toml");
+
+/// How a sprite whose size differs from the terminal's glyph size is fitted into a cell when it is loaded.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Scaling {
+    /// Keep the sprite as it is, the renderer stretches it over the cell.
+    None,
+    /// Resize the sprite to exactly the glyph size.
+    Stretch,
+    /// Resize the sprite as large as possible while keeping its aspect ratio, centered in the cell.
+    Fit,
+    /// Keep the sprite's size and center it in the cell, cropping it if it is larger than the cell.
+    Center,
+}
+
+impl Scaling {
+    /// Apply this scaling rule to an image, producing an image of the given glyph size.
+    pub fn apply(self, image: RgbaImage, glyph_size: Point) -> RgbaImage {
+        let (width, height) = image.dimensions();
+        if (width, height) == (glyph_size.x, glyph_size.y) {
+            return image;
+        }
+
+        match self {
+            Scaling::None => image,
+            Scaling::Stretch => {
+                image::imageops::resize(&image, glyph_size.x, glyph_size.y, FilterType::Nearest)
+            }
+            Scaling::Fit => {
+                let factor = f32::min(
+                    glyph_size.x as f32 / width as f32,
+                    glyph_size.y as f32 / height as f32,
+                );
+                let new_width = ((width as f32 * factor) as u32).max(1);
+                let new_height = ((height as f32 * factor) as u32).max(1);
+                let resized =
+                    image::imageops::resize(&image, new_width, new_height, FilterType::Nearest);
+                center_in_cell(resized, glyph_size)
+            }
+            Scaling::Center => center_in_cell(image, glyph_size),
+        }
+    }
+}
+
+/// Center an image on a transparent image of the glyph size, cropping any overhang evenly.
+fn center_in_cell(mut image: RgbaImage, glyph_size: Point) -> RgbaImage {
+    let (width, height) = image.dimensions();
+    let crop_x = width.saturating_sub(glyph_size.x) / 2;
+    let crop_y = height.saturating_sub(glyph_size.y) / 2;
+    let cropped = image::imageops::crop(
+        &mut image,
+        crop_x,
+        crop_y,
+        width.min(glyph_size.x),
+        height.min(glyph_size.y),
+    )
+    .to_image();
+
+    let mut cell = RgbaImage::new(glyph_size.x, glyph_size.y);
+    image::imageops::overlay(
+        &mut cell,
+        &cropped,
+        (glyph_size.x - cropped.width()) / 2,
+        (glyph_size.y - cropped.height()) / 2,
+    );
+    cell
+}
+
+/// Where a group of sprites in a SpriteMap was loaded from, so the map can be reloaded.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SpriteSource {
+    Sheet {
+        path: PathBuf,
+        namespace: Option<String>,
+        glyph_size: Point,
+        scaling: Scaling,
+    },
+    Files {
+        folder: PathBuf,
+        namespace: Option<String>,
+        glyph_size: Point,
+        scaling: Scaling,
+    },
+    #[cfg(feature = "default-font")]
+    DefaultFont {
+        namespace: Option<String>,
+        glyph_size: Point,
+        scaling: Scaling,
+    },
+}
+
+/// Load a png file as an rgba image.
+fn load_png<P: AsRef<Path>>(path: P) -> OozeResult<RgbaImage> {
+    let r = BufReader::new(File::open(path)?);
+    Ok(image::load(r, image::PNG)?.to_rgba())
+}
+
 /// Maps strings to a Sprite and handles loading of sprites from files or spritesheets.
+/// Several sheets and folders can be merged into one map, optionally under a namespace so that e.g. the "wall" sprite of a sheet loaded with the namespace "terrain" is accessed as "terrain:wall".
 pub struct SpriteMap {
     sprite_map: HashMap<String, Sprite>,
+    sources: Vec<SpriteSource>,
 }
 
 impl SpriteMap {
+    /// Create an empty SpriteMap.
+    pub fn new() -> SpriteMap {
+        SpriteMap {
+            sprite_map: HashMap::new(),
+            sources: Vec::new(),
+        }
+    }
+
     /// Create a SpriteMap and load from individual png images located in the given folder and sub-folder.
     /// Each Sprite is stored in the map under it's filename without an extension. e.g. the sprite loaded from "test.png" would be accessed as SpriteMap.get("test").
     pub fn from_files(display: &Display, resource_folder: &Path) -> OozeResult<SpriteMap> {
-        let mut map = HashMap::new();
-        for file_path in
-            glob((resource_folder.to_str().unwrap().to_owned() + r#"\**\*.png"#).as_str()).unwrap()
-        {
-            let path = file_path.unwrap();
-            let id = String::from_str(
-                path.file_stem()
-                    .ok_or(OozeError::PathError)?
-                    .to_str()
-                    .ok_or(OozeError::PathError)?,
-            )
-            .unwrap();
-            map.insert(id, Sprite::new(path, display)?);
-        }
-
-        let sprite_map = SpriteMap { sprite_map: map };
+        let mut sprite_map = SpriteMap::new();
+        sprite_map.add_files(
+            display,
+            resource_folder,
+            None,
+            Point::new(0, 0),
+            Scaling::None,
+        )?;
 
         Ok(sprite_map)
     }
@@ -91,43 +197,222 @@ impl SpriteMap {
     /// It is a .toml file that contains the locations and names of each sprite in the image.
     /// See the resources folder for examples.
     pub fn from_sheet(display: &Display, sheet_path: &Path) -> OozeResult<SpriteMap> {
-        let metadata_path = sheet_path.with_extension("toml");
+        let mut sprite_map = SpriteMap::new();
+        sprite_map.add_sheet(display, sheet_path, None, Point::new(0, 0), Scaling::None)?;
+
+        Ok(sprite_map)
+    }
 
-        let root_table = read_to_string(metadata_path)?.parse::<Value>()?;
+    /// Load individual png images located in the given folder and sub-folders into this SpriteMap.
+    /// Each Sprite is stored under its filename without an extension, prefixed with "namespace:" if a namespace is given.
+    /// Sprites are fitted to glyph_size with the given Scaling rule. Nothing is added if any of them fails to load or conflicts.
+    pub fn add_files(
+        &mut self,
+        display: &Display,
+        resource_folder: &Path,
+        namespace: Option<&str>,
+        glyph_size: Point,
+        scaling: Scaling,
+    ) -> OozeResult<()> {
+        let pattern = resource_folder.join("**").join("*.png");
+        let mut images = HashMap::new();
+        for file_path in glob(pattern.to_str().ok_or(OozeError::PathError)?)? {
+            let path = file_path?;
+            let name = path
+                .file_stem()
+                .and_then(OsStr::to_str)
+                .ok_or(OozeError::PathError)?;
+            let image = scaling.apply(load_png(&path)?, glyph_size);
+            insert_new(&mut images, namespaced(namespace, name), image)?;
+        }
+        self.add_images(display, images)?;
 
-        let sprite_width = root_table["dimensions"]["sprite_width"]
-            .as_integer()
-            .ok_or(OozeError::GenericError)? as u32;
-        let sprite_height = root_table["dimensions"]["sprite_height"]
-            .as_integer()
-            .ok_or(OozeError::GenericError)? as u32;
+        self.sources.push(SpriteSource::Files {
+            folder: resource_folder.to_path_buf(),
+            namespace: namespace.map(str::to_string),
+            glyph_size,
+            scaling,
+        });
 
-        let sprites = root_table["sprites"]
-            .as_table()
-            .ok_or(OozeError::GenericError)?;
+        Ok(())
+    }
+
+    /// Load the sprites from a spritesheet and its metadata file into this SpriteMap.
+    /// Each Sprite is stored under its name in the metadata, prefixed with "namespace:" if a namespace is given.
+    /// The sheet's cells may be any size, they are fitted to glyph_size with the given Scaling rule.
+    pub fn add_sheet(
+        &mut self,
+        display: &Display,
+        sheet_path: &Path,
+        namespace: Option<&str>,
+        glyph_size: Point,
+        scaling: Scaling,
+    ) -> OozeResult<()> {
+        let metadata = read_to_string(sheet_path.with_extension("toml"))?;
+
+        self.add_sheet_image(
+            display,
+            load_png(sheet_path)?,
+            &metadata,
+            namespace,
+            glyph_size,
+            scaling,
+        )?;
+
+        self.sources.push(SpriteSource::Sheet {
+            path: sheet_path.to_path_buf(),
+            namespace: namespace.map(str::to_string),
+            glyph_size,
+            scaling,
+        });
+
+        Ok(())
+    }
+
+    /// Load the built-in CP437 font sheet into this SpriteMap.
+    /// Each sprite id is the character it shows, e.g. "A", "@" or "╔".
+    #[cfg(feature = "default-font")]
+    pub fn add_default_font(
+        &mut self,
+        display: &Display,
+        namespace: Option<&str>,
+        glyph_size: Point,
+        scaling: Scaling,
+    ) -> OozeResult<()> {
+        let sheet = image::load_from_memory_with_format(DEFAULT_FONT_SHEET, image::PNG)?.to_rgba();
+
+        self.add_sheet_image(
+            display,
+            sheet,
+            DEFAULT_FONT_METADATA,
+            namespace,
+            glyph_size,
+            scaling,
+        )?;
+
+        self.sources.push(SpriteSource::DefaultFont {
+            namespace: namespace.map(str::to_string),
+            glyph_size,
+            scaling,
+        });
+
+        Ok(())
+    }
 
-        let mut map = HashMap::new();
-        for name in sprites.keys() {
-            let id = name.clone();
-            let x = sprites[name][0].as_integer().unwrap() as u32;
-            let y = sprites[name][1].as_integer().unwrap() as u32;
-
-            map.insert(
-                id,
-                Sprite::from_sheet(
-                    sheet_path,
-                    display,
-                    x * sprite_width,
-                    y * sprite_height,
-                    sprite_width,
-                    sprite_height,
-                )?,
-            );
+    /// Load the sprites from an already loaded spritesheet and the contents of its metadata file into this SpriteMap.
+    /// Sprites loaded this way are not reloaded by SpriteMap::reload. Nothing is added if any of them fails to load or conflicts.
+    pub fn add_sheet_image(
+        &mut self,
+        display: &Display,
+        mut sheet: RgbaImage,
+        metadata: &str,
+        namespace: Option<&str>,
+        glyph_size: Point,
+        scaling: Scaling,
+    ) -> OozeResult<()> {
+        let images = sheet_images(&mut sheet, metadata, namespace, glyph_size, scaling)?;
+        self.add_images(display, images)
+    }
+
+    /// Turn images into Sprites under their ids. Nothing is added if any id is already taken.
+    fn add_images(
+        &mut self,
+        display: &Display,
+        images: HashMap<String, RgbaImage>,
+    ) -> OozeResult<()> {
+        let mut loaded = HashMap::with_capacity(images.len());
+        for (id, image) in images {
+            loaded.insert(id, Sprite::from_image(image, display)?);
         }
+        merge_new(&mut self.sprite_map, loaded)
+    }
 
-        let sprite_map = SpriteMap { sprite_map: map };
+    /// Add a Sprite under the given id. Fails if the id is already taken.
+    pub fn insert(&mut self, id: String, sprite: Sprite) -> OozeResult<()> {
+        insert_new(&mut self.sprite_map, id, sprite)
+    }
 
-        Ok(sprite_map)
+    /// Move all the Sprites of another SpriteMap into this one. Fails without changing this map if any ids conflict.
+    pub fn merge(&mut self, other: SpriteMap) -> OozeResult<()> {
+        merge_new(&mut self.sprite_map, other.sprite_map)?;
+        self.sources.extend(other.sources);
+
+        Ok(())
+    }
+
+    /// Load every sheet and folder this SpriteMap was built from again, replacing the sprites in place.
+    /// Nothing is changed if any of them fails to load. Sprites that disappeared from their source are kept.
+    pub fn reload(&mut self, display: &Display) -> OozeResult<()> {
+        let mut fresh = SpriteMap::new();
+        for source in &self.sources {
+            match source {
+                SpriteSource::Sheet {
+                    path,
+                    namespace,
+                    glyph_size,
+                    scaling,
+                } => fresh.add_sheet(display, path, namespace.as_deref(), *glyph_size, *scaling)?,
+                SpriteSource::Files {
+                    folder,
+                    namespace,
+                    glyph_size,
+                    scaling,
+                } => {
+                    fresh.add_files(display, folder, namespace.as_deref(), *glyph_size, *scaling)?
+                }
+                #[cfg(feature = "default-font")]
+                SpriteSource::DefaultFont {
+                    namespace,
+                    glyph_size,
+                    scaling,
+                } => {
+                    fresh.add_default_font(display, namespace.as_deref(), *glyph_size, *scaling)?
+                }
+            }
+        }
+
+        self.sprite_map.extend(fresh.sprite_map);
+
+        Ok(())
+    }
+
+    /// The sheets and folders this SpriteMap was loaded from.
+    pub fn sources(&self) -> &[SpriteSource] {
+        &self.sources
+    }
+
+    /// The files that sprites of this SpriteMap were loaded from: each sheet and its metadata, and each folder of individual images.
+    pub fn source_paths(&self) -> Vec<PathBuf> {
+        let mut result = Vec::new();
+        for source in &self.sources {
+            match source {
+                SpriteSource::Sheet { path, .. } => {
+                    result.push(path.clone());
+                    result.push(path.with_extension("toml"));
+                }
+                SpriteSource::Files { folder, .. } => {
+                    result.push(folder.clone());
+                    if let Some(pattern) = folder.join("**").join("*.png").to_str() {
+                        if let Ok(paths) = glob(pattern) {
+                            result.extend(paths.filter_map(Result::ok));
+                        }
+                    }
+                }
+                #[cfg(feature = "default-font")]
+                SpriteSource::DefaultFont { .. } => (),
+            }
+        }
+        result
+    }
+
+    /// Check if a Sprite with the given id is in this SpriteMap.
+    pub fn contains(&self, id: &str) -> bool {
+        self.sprite_map.contains_key(id)
+    }
+
+    /// Returns an iterator over the ids of all the Sprites in this SpriteMap.
+    pub fn ids(&self) -> impl Iterator<Item = &String> {
+        self.sprite_map.keys()
     }
 
     /// Get the sprite with the given id from this SpriteMap.
@@ -145,3 +430,236 @@ impl SpriteMap {
         Ok(self.get(id)?)
     }
 }
+
+impl Default for SpriteMap {
+    fn default() -> SpriteMap {
+        SpriteMap::new()
+    }
+}
+
+/// Cut the sprites out of a spritesheet as its metadata describes and fit them to glyph_size with the given Scaling rule.
+/// Each image is keyed by its name in the metadata, prefixed with "namespace:" if a namespace is given.
+fn sheet_images(
+    sheet: &mut RgbaImage,
+    metadata: &str,
+    namespace: Option<&str>,
+    glyph_size: Point,
+    scaling: Scaling,
+) -> OozeResult<HashMap<String, RgbaImage>> {
+    let root_table = metadata.parse::<Value>()?;
+
+    let dimensions = root_table.get("dimensions");
+    let sprite_width = toml_u32(dimensions.and_then(|d| d.get("sprite_width")))?;
+    let sprite_height = toml_u32(dimensions.and_then(|d| d.get("sprite_height")))?;
+    if sprite_width == 0 || sprite_height == 0 {
+        return Err(Box::new(OozeError::BadFormatError(
+            "sprites need a width and height of at least 1".to_string(),
+        )));
+    }
+
+    let sprites = root_table
+        .get("sprites")
+        .and_then(Value::as_table)
+        .ok_or(OozeError::GenericError)?;
+
+    let mut images = HashMap::new();
+    for (name, cell) in sprites {
+        let x = toml_u32(cell.get(0))?;
+        let y = toml_u32(cell.get(1))?;
+        let left = cell_start(x, sprite_width, sheet.width())?;
+        let top = cell_start(y, sprite_height, sheet.height())?;
+
+        let image = image::imageops::crop(sheet, left, top, sprite_width, sprite_height).to_image();
+        images.insert(
+            namespaced(namespace, name),
+            scaling.apply(image, glyph_size),
+        );
+    }
+
+    Ok(images)
+}
+
+/// Read a toml integer as a u32. Fails for missing values, other types and integers out of range.
+fn toml_u32(value: Option<&Value>) -> OozeResult<u32> {
+    let integer = value
+        .and_then(Value::as_integer)
+        .ok_or(OozeError::GenericError)?;
+    Ok(u32::try_from(integer).map_err(|_| OozeError::OutOfBoundsError)?)
+}
+
+/// The first pixel of the cell at index along one side of a sheet. Fails if the cell doesn't fit in length pixels.
+fn cell_start(index: u32, cell_length: u32, length: u32) -> OozeResult<u32> {
+    let end = index
+        .checked_add(1)
+        .and_then(|cells| cells.checked_mul(cell_length));
+    match end {
+        Some(end) if end <= length => Ok(end - cell_length),
+        _ => Err(Box::new(OozeError::OutOfBoundsError)),
+    }
+}
+
+/// Add a value under an id. Fails if the id is already taken.
+fn insert_new<T>(map: &mut HashMap<String, T>, id: String, value: T) -> OozeResult<()> {
+    if map.contains_key(&id) {
+        return Err(Box::new(OozeError::SpriteConflictError(id)));
+    }
+    map.insert(id, value);
+
+    Ok(())
+}
+
+/// Move all the values of other into map. Fails without changing map if any ids conflict.
+fn merge_new<T>(map: &mut HashMap<String, T>, other: HashMap<String, T>) -> OozeResult<()> {
+    if let Some(id) = other.keys().find(|id| map.contains_key(*id)) {
+        return Err(Box::new(OozeError::SpriteConflictError(id.clone())));
+    }
+    map.extend(other);
+
+    Ok(())
+}
+
+/// Build a sprite id from an optional namespace and a name, e.g. "terrain:wall".
+fn namespaced(namespace: Option<&str>, name: &str) -> String {
+    match namespace {
+        Some(namespace) => format!("{}:{}", namespace, name),
+        None => name.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Rgba;
+
+    const RED: Rgba<u8> = Rgba([255, 0, 0, 255]);
+    const BLUE: Rgba<u8> = Rgba([0, 0, 255, 255]);
+    const CLEAR: Rgba<u8> = Rgba([0, 0, 0, 0]);
+
+    /// A sheet of two 2x2 cells side by side, red on the left and blue on the right.
+    fn sheet() -> RgbaImage {
+        RgbaImage::from_fn(4, 2, |x, _| if x < 2 { RED } else { BLUE })
+    }
+
+    fn same(a: &RgbaImage, b: &RgbaImage) -> bool {
+        a.dimensions() == b.dimensions() && a.pixels().eq(b.pixels())
+    }
+
+    fn metadata(sprites: &str) -> String {
+        format!(
+            "[dimensions]\nsprite_width = 2\nsprite_height = 2\n\n[sprites]\n{}\n",
+            sprites
+        )
+    }
+
+    fn load(sprites: &str, namespace: Option<&str>) -> OozeResult<HashMap<String, RgbaImage>> {
+        sheet_images(
+            &mut sheet(),
+            &metadata(sprites),
+            namespace,
+            Point::new(2, 2),
+            Scaling::None,
+        )
+    }
+
+    #[test]
+    fn sheets_are_cut_into_cells() {
+        let images = load("wall = [0, 0]\nwater = [1, 0]", Some("terrain")).unwrap();
+
+        assert_eq!(images.len(), 2);
+        assert_eq!(images["terrain:wall"].get_pixel(1, 1), &RED);
+        assert_eq!(images["terrain:water"].get_pixel(0, 0), &BLUE);
+    }
+
+    #[test]
+    fn cells_outside_the_sheet_are_errors() {
+        assert!(load("wall = [2, 0]", None).is_err());
+        assert!(load("wall = [0, 1]", None).is_err());
+        assert!(load("wall = [-1, 0]", None).is_err());
+        // (x + 1) * sprite_width doesn't fit in a u32
+        assert!(load("wall = [4294967295, 0]", None).is_err());
+        assert!(load("wall = [2147483648, 0]", None).is_err());
+        assert!(load("wall = [0]", None).is_err());
+        assert!(load("wall = \"here\"", None).is_err());
+
+        let huge_cells = "[dimensions]\nsprite_width = 4294967296\nsprite_height = 2\n\n[sprites]\nwall = [0, 0]\n";
+        let images = sheet_images(
+            &mut sheet(),
+            huge_cells,
+            None,
+            Point::new(2, 2),
+            Scaling::None,
+        );
+        assert!(images.is_err());
+    }
+
+    #[test]
+    fn conflicting_loads_add_nothing() {
+        let mut map = load("wall = [0, 0]", Some("terrain")).unwrap();
+
+        let conflicting = load("floor = [1, 0]\nwall = [1, 0]", Some("terrain")).unwrap();
+        assert!(merge_new(&mut map, conflicting).is_err());
+        assert_eq!(map.len(), 1);
+        assert_eq!(map["terrain:wall"].get_pixel(0, 0), &RED);
+
+        let other_namespace = load("floor = [1, 0]\nwall = [1, 0]", Some("items")).unwrap();
+        merge_new(&mut map, other_namespace).unwrap();
+        assert_eq!(map.len(), 3);
+
+        assert!(insert_new(&mut map, "items:wall".to_string(), sheet()).is_err());
+        assert_eq!(map["items:wall"].dimensions(), (2, 2));
+    }
+
+    #[test]
+    fn scaling_none_and_stretch() {
+        let image = RgbaImage::from_pixel(4, 2, RED);
+        let glyph_size = Point::new(8, 8);
+
+        assert!(same(
+            &Scaling::None.apply(image.clone(), glyph_size),
+            &image
+        ));
+
+        let stretched = Scaling::Stretch.apply(image.clone(), glyph_size);
+        assert_eq!(stretched.dimensions(), (8, 8));
+        assert!(stretched.pixels().all(|pixel| *pixel == RED));
+
+        // images of the glyph size are kept by every rule
+        let cell = RgbaImage::from_pixel(8, 8, BLUE);
+        for scaling in &[
+            Scaling::None,
+            Scaling::Stretch,
+            Scaling::Fit,
+            Scaling::Center,
+        ] {
+            assert!(same(&scaling.apply(cell.clone(), glyph_size), &cell));
+        }
+    }
+
+    #[test]
+    fn scaling_fit_keeps_the_aspect_ratio() {
+        let fitted = Scaling::Fit.apply(RgbaImage::from_pixel(4, 2, RED), Point::new(8, 8));
+
+        // scaled to 8x4 and centered, with clear rows above and below
+        assert_eq!(fitted.dimensions(), (8, 8));
+        assert_eq!(fitted.get_pixel(0, 1), &CLEAR);
+        assert_eq!(fitted.get_pixel(0, 2), &RED);
+        assert_eq!(fitted.get_pixel(7, 5), &RED);
+        assert_eq!(fitted.get_pixel(7, 6), &CLEAR);
+    }
+
+    #[test]
+    fn scaling_center_pads_and_crops() {
+        let centered = Scaling::Center.apply(RgbaImage::from_pixel(4, 2, RED), Point::new(8, 8));
+        assert_eq!(centered.dimensions(), (8, 8));
+        assert_eq!(centered.get_pixel(2, 3), &RED);
+        assert_eq!(centered.get_pixel(5, 4), &RED);
+        assert_eq!(centered.get_pixel(1, 3), &CLEAR);
+        assert_eq!(centered.get_pixel(2, 5), &CLEAR);
+
+        // the middle of a larger image is kept
+        let large = RgbaImage::from_fn(12, 8, |x, _| if (2..10).contains(&x) { RED } else { BLUE });
+        let cropped = Scaling::Center.apply(large, Point::new(8, 8));
+        assert_eq!(cropped.dimensions(), (8, 8));
+        assert!(cropped.pixels().all(|pixel| *pixel == RED));
+    }
+}
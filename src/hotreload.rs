@@ -0,0 +1,59 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+/// Watches files for changes by polling their modification times.
+/// Watching a folder notices files being added to or removed from it.
+pub struct FileWatcher {
+    pub interval: Duration,
+
+    files: HashMap<PathBuf, Option<SystemTime>>,
+    last_poll: Instant,
+}
+
+impl FileWatcher {
+    /// Create a FileWatcher that checks its files at most once per interval.
+    pub fn new(interval: Duration) -> FileWatcher {
+        FileWatcher {
+            interval,
+            files: HashMap::new(),
+            last_poll: Instant::now(),
+        }
+    }
+
+    /// Start watching the given path. Paths that don't exist yet are reported once they appear.
+    pub fn watch<P: AsRef<Path>>(&mut self, path: P) {
+        let path = path.as_ref().to_path_buf();
+        let modified = modified_time(&path);
+        self.files.entry(path).or_insert(modified);
+    }
+
+    /// Stop watching all paths.
+    pub fn clear(&mut self) {
+        self.files.clear();
+    }
+
+    /// Returns the watched paths that changed since the last poll, or nothing if the interval hasn't passed yet.
+    pub fn poll(&mut self) -> Vec<PathBuf> {
+        if self.last_poll.elapsed() < self.interval {
+            return Vec::new();
+        }
+        self.last_poll = Instant::now();
+
+        let mut changed = Vec::new();
+        for (path, last_modified) in self.files.iter_mut() {
+            let modified = modified_time(path);
+            if modified != *last_modified {
+                *last_modified = modified;
+                changed.push(path.clone());
+            }
+        }
+        changed
+    }
+}
+
+/// The modification time of the file at path, or None if it can't be read.
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|meta| meta.modified()).ok()
+}
@@ -0,0 +1,152 @@
+use glium::glutin::dpi::LogicalSize;
+use glium::glutin::{
+    ElementState, Event, KeyboardInput, MouseScrollDelta, VirtualKeyCode, WindowEvent,
+};
+
+use crate::geometry::Point;
+
+/// A key on the keyboard, independent of the backend that read it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Key {
+    Char(char),
+    Enter,
+    Escape,
+    Backspace,
+    Delete,
+    Tab,
+    BackTab,
+    Up,
+    Down,
+    Left,
+    Right,
+    Home,
+    End,
+    PageUp,
+    PageDown,
+    F(u8),
+}
+
+/// An input event, independent of the backend that read it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum InputEvent {
+    /// A key was pressed, with whether ctrl and alt were held. Shift is already applied to Char keys.
+    KeyPressed { key: Key, ctrl: bool, alt: bool },
+    /// The window or text terminal was resized to the given size, in pixels or character cells depending on the backend.
+    Resized(u32, u32),
+    /// The mouse wheel was turned by this many notches, positive away from the user.
+    MouseWheel(i32),
+    /// The mouse moved over the terminal cell at this Point.
+    MouseMoved(Point),
+    /// The user asked to close the game, by closing the window or pressing ctrl-c in a text terminal.
+    Close,
+}
+
+impl InputEvent {
+    /// The key pressed, if this is a key event.
+    pub fn key(&self) -> Option<Key> {
+        match self {
+            InputEvent::KeyPressed { key, .. } => Some(*key),
+            _ => None,
+        }
+    }
+}
+
+/// Convert a window event to an InputEvent. Typed text arrives as Char keys, other keys as their own Key.
+/// Mouse positions become the cell under the mouse, for a terminal of term_size stretched over a window of window_size.
+/// Key releases, mouse buttons and keys ooze doesn't know about are left out.
+pub fn from_glutin(
+    event: &Event,
+    window_size: LogicalSize,
+    term_size: Point,
+) -> Option<InputEvent> {
+    let window_event = match event {
+        Event::WindowEvent { event, .. } => event,
+        _ => return None,
+    };
+
+    match window_event {
+        WindowEvent::CloseRequested => Some(InputEvent::Close),
+        WindowEvent::Resized(size) => {
+            Some(InputEvent::Resized(size.width as u32, size.height as u32))
+        }
+        WindowEvent::CursorMoved { position, .. } => {
+            if window_size.width <= 0.0 || window_size.height <= 0.0 {
+                return None;
+            }
+            let x = position.x / window_size.width * f64::from(term_size.x);
+            let y = position.y / window_size.height * f64::from(term_size.y);
+            if x < 0.0 || y < 0.0 || x >= f64::from(term_size.x) || y >= f64::from(term_size.y) {
+                return None;
+            }
+            // window positions start at the top-left, terminal cells at the bottom-left
+            Some(InputEvent::MouseMoved(Point::new(
+                x as u32,
+                term_size.y - 1 - y as u32,
+            )))
+        }
+        WindowEvent::MouseWheel { delta, .. } => {
+            let notches = match delta {
+                MouseScrollDelta::LineDelta(_, y) => y.round() as i32,
+                // touchpads scroll in pixels, count a notch per glyph-ish step
+                MouseScrollDelta::PixelDelta(position) => (position.y / 16.0).round() as i32,
+            };
+            if notches == 0 {
+                None
+            } else {
+                Some(InputEvent::MouseWheel(notches))
+            }
+        }
+        WindowEvent::ReceivedCharacter(c) if !c.is_control() => Some(InputEvent::KeyPressed {
+            key: Key::Char(*c),
+            ctrl: false,
+            alt: false,
+        }),
+        WindowEvent::KeyboardInput {
+            input:
+                KeyboardInput {
+                    state: ElementState::Pressed,
+                    virtual_keycode: Some(code),
+                    modifiers,
+                    ..
+                },
+            ..
+        } => {
+            let key = match code {
+                VirtualKeyCode::Return | VirtualKeyCode::NumpadEnter => Key::Enter,
+                VirtualKeyCode::Escape => Key::Escape,
+                VirtualKeyCode::Back => Key::Backspace,
+                VirtualKeyCode::Delete => Key::Delete,
+                VirtualKeyCode::Tab if modifiers.shift => Key::BackTab,
+                VirtualKeyCode::Tab => Key::Tab,
+                VirtualKeyCode::Up => Key::Up,
+                VirtualKeyCode::Down => Key::Down,
+                VirtualKeyCode::Left => Key::Left,
+                VirtualKeyCode::Right => Key::Right,
+                VirtualKeyCode::Home => Key::Home,
+                VirtualKeyCode::End => Key::End,
+                VirtualKeyCode::PageUp => Key::PageUp,
+                VirtualKeyCode::PageDown => Key::PageDown,
+                VirtualKeyCode::F1 => Key::F(1),
+                VirtualKeyCode::F2 => Key::F(2),
+                VirtualKeyCode::F3 => Key::F(3),
+                VirtualKeyCode::F4 => Key::F(4),
+                VirtualKeyCode::F5 => Key::F(5),
+                VirtualKeyCode::F6 => Key::F(6),
+                VirtualKeyCode::F7 => Key::F(7),
+                VirtualKeyCode::F8 => Key::F(8),
+                VirtualKeyCode::F9 => Key::F(9),
+                VirtualKeyCode::F10 => Key::F(10),
+                VirtualKeyCode::F11 => Key::F(11),
+                VirtualKeyCode::F12 => Key::F(12),
+                // everything else arrives as typed text
+                _ => return None,
+            };
+            Some(InputEvent::KeyPressed {
+                key,
+                ctrl: modifiers.ctrl,
+                alt: modifiers.alt,
+            })
+        }
+        _ => None,
+    }
+}
@@ -1,8 +1,20 @@
+pub mod animation;
 pub mod app;
+#[cfg(feature = "ecs")]
+pub mod ecs;
 pub mod error;
+pub mod fog;
+pub mod formats;
+pub mod frame;
 pub mod geometry;
 pub mod graphics;
+pub mod hotreload;
+pub mod input;
+pub mod lighting;
 pub mod terminal;
+#[cfg(feature = "tty")]
+pub mod tty;
+pub mod ui;
 
 #[cfg(test)]
 mod tests {
@@ -0,0 +1,341 @@
+use crate::geometry::{line, Point, Rect};
+use crate::terminal::Panel;
+
+/// How a Light gets dimmer towards the edge of its radius.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Falloff {
+    /// Full strength up to the radius.
+    Constant,
+    /// Dims evenly to nothing at the radius.
+    Linear,
+    /// Dims quickly near the Light and slowly towards the radius, like a torch.
+    Quadratic,
+}
+
+impl Falloff {
+    /// The share of a Light's strength left at distance from it.
+    pub fn attenuation(self, distance: f32, radius: f32) -> f32 {
+        if radius <= 0.0 || distance > radius {
+            return 0.0;
+        }
+        let left = 1.0 - distance / radius;
+        match self {
+            Falloff::Constant => 1.0,
+            Falloff::Linear => left,
+            Falloff::Quadratic => left * left,
+        }
+    }
+}
+
+/// A light source on a cell, lighting the cells around it that it has a clear line to.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Light {
+    pub position: Point,
+    pub color: [f32; 3],
+    /// Multiplies the color, above 1.0 lights cells brighter than their own colors.
+    pub intensity: f32,
+    /// How far the Light reaches, in cells.
+    pub radius: f32,
+    pub falloff: Falloff,
+}
+
+impl Light {
+    /// Create a new Light of intensity 1.0 with quadratic falloff.
+    pub fn new(position: Point, color: [f32; 3], radius: f32) -> Light {
+        Light {
+            position,
+            color,
+            intensity: 1.0,
+            radius,
+            falloff: Falloff::Quadratic,
+        }
+    }
+
+    /// Returns a copy of this Light with the given intensity.
+    pub fn with_intensity(self, intensity: f32) -> Light {
+        let mut new = self;
+        new.intensity = intensity;
+        new
+    }
+
+    /// Returns a copy of this Light with the given falloff.
+    pub fn with_falloff(self, falloff: Falloff) -> Light {
+        let mut new = self;
+        new.falloff = falloff;
+        new
+    }
+}
+
+/// The light on each cell of a map, worked out from Lights and ambient light, for darkening the glyphs of a Panel.
+/// Cells lit before but not now are remembered and drawn with the memory color instead, so explored parts of the map stay readable.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LightMap {
+    pub size: Point,
+    /// The light every cell gets, lit or not.
+    pub ambient: [f32; 3],
+    /// The light remembered cells are drawn with.
+    pub memory: [f32; 3],
+
+    values: Vec<Vec<[f32; 3]>>,
+    lit: Vec<Vec<bool>>,
+    explored: Vec<Vec<bool>>,
+}
+
+impl LightMap {
+    /// Create a new dark LightMap of the given size with no ambient light and a dim blue memory color.
+    pub fn new(size: Point) -> LightMap {
+        let (width, height) = (size.x as usize, size.y as usize);
+        LightMap {
+            size,
+            ambient: [0.0, 0.0, 0.0],
+            memory: [0.2, 0.2, 0.35],
+            values: vec![vec![[0.0; 3]; height]; width],
+            lit: vec![vec![false; height]; width],
+            explored: vec![vec![false; height]; width],
+        }
+    }
+
+    /// Returns a copy of this LightMap with the given ambient light.
+    pub fn with_ambient(self, ambient: [f32; 3]) -> LightMap {
+        let mut new = self;
+        new.ambient = ambient;
+        new
+    }
+
+    /// Returns a copy of this LightMap with the given memory color.
+    pub fn with_memory(self, memory: [f32; 3]) -> LightMap {
+        let mut new = self;
+        new.memory = memory;
+        new
+    }
+
+    /// Work out the light on every cell from the given Lights. is_opaque tells if a cell blocks light,
+    /// opaque cells are lit themselves but hide the cells behind them. Lit cells are remembered.
+    pub fn compute<F: Fn(Point) -> bool>(&mut self, lights: &[Light], is_opaque: F) {
+        let bounds = Rect::of_size(self.size);
+        for x in 0..self.size.x as usize {
+            for y in 0..self.size.y as usize {
+                self.values[x][y] = self.ambient;
+                self.lit[x][y] = false;
+            }
+        }
+
+        for light in lights {
+            let reach = light.radius.max(0.0).ceil() as u32;
+            let (center_x, center_y) = (light.position.x, light.position.y);
+            let left = center_x.saturating_sub(reach);
+            let bottom = center_y.saturating_sub(reach);
+            let right = (center_x + reach).min(self.size.x.saturating_sub(1));
+            let top = (center_y + reach).min(self.size.y.saturating_sub(1));
+
+            for x in left..=right {
+                for y in bottom..=top {
+                    let point = Point::new(x, y);
+                    if !bounds.contains_point(point) {
+                        continue;
+                    }
+                    let (dx, dy) = (x as f32 - center_x as f32, y as f32 - center_y as f32);
+                    let distance = (dx * dx + dy * dy).sqrt();
+                    let strength =
+                        light.falloff.attenuation(distance, light.radius) * light.intensity;
+                    if strength <= 0.0 || !clear_line(light.position, point, &is_opaque) {
+                        continue;
+                    }
+
+                    let value = &mut self.values[x as usize][y as usize];
+                    for (channel, color) in value.iter_mut().zip(&light.color) {
+                        *channel += color * strength;
+                    }
+                    self.lit[x as usize][y as usize] = true;
+                    self.explored[x as usize][y as usize] = true;
+                }
+            }
+        }
+    }
+
+    /// The light on a cell, or None if it is outside this LightMap.
+    pub fn light(&self, point: Point) -> Option<[f32; 3]> {
+        self.values
+            .get(point.x as usize)
+            .and_then(|column| column.get(point.y as usize))
+            .copied()
+    }
+
+    /// Check if any Light reached a cell on the last compute.
+    pub fn is_lit(&self, point: Point) -> bool {
+        self.lit
+            .get(point.x as usize)
+            .and_then(|column| column.get(point.y as usize))
+            .copied()
+            .unwrap_or(false)
+    }
+
+    /// Check if any Light ever reached a cell.
+    pub fn is_explored(&self, point: Point) -> bool {
+        self.explored
+            .get(point.x as usize)
+            .and_then(|column| column.get(point.y as usize))
+            .copied()
+            .unwrap_or(false)
+    }
+
+    /// Forget which cells were lit before, e.g. when entering a new level.
+    pub fn forget(&mut self) {
+        for column in &mut self.explored {
+            for explored in column.iter_mut() {
+                *explored = false;
+            }
+        }
+    }
+
+    /// The light a cell is drawn with: its light if lit, the memory color if it was lit before, the ambient light otherwise.
+    pub fn drawn_light(&self, point: Point) -> [f32; 3] {
+        if self.is_lit(point) {
+            self.light(point).unwrap_or(self.ambient)
+        } else if self.is_explored(point) {
+            self.memory
+        } else {
+            self.ambient
+        }
+    }
+
+    /// Light the glyphs of a Panel, the cells of this LightMap being the cells of the Panel.
+    /// Only changes how the glyphs are drawn, not their colors. Sub-panels are left alone.
+    pub fn apply(&self, panel: &mut Panel) {
+        for column in &mut panel.contents {
+            for glyph in column.iter_mut() {
+                glyph.effects.light = self.drawn_light(glyph.location);
+            }
+        }
+    }
+}
+
+/// Take the light off the glyphs of a Panel, see LightMap::apply.
+pub fn clear_lighting(panel: &mut Panel) {
+    for column in &mut panel.contents {
+        for glyph in column.iter_mut() {
+            glyph.effects.light = [1.0, 1.0, 1.0];
+        }
+    }
+}
+
+/// Check that no cell between two cells blocks the line from one to the other. The cells themselves may be opaque.
+fn clear_line<F: Fn(Point) -> bool>(from: Point, to: Point, is_opaque: &F) -> bool {
+    let points = line(from, to);
+    let between = points.len().saturating_sub(1);
+    points
+        .iter()
+        .take(between)
+        .skip(1)
+        .all(|point| !is_opaque(*point))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::Dimensions;
+
+    const WHITE: [f32; 3] = [1.0, 1.0, 1.0];
+
+    #[test]
+    fn falloff_reaches_the_radius_and_no_further() {
+        assert_eq!(Falloff::Constant.attenuation(0.0, 4.0), 1.0);
+        assert_eq!(Falloff::Constant.attenuation(4.0, 4.0), 1.0);
+        assert_eq!(Falloff::Linear.attenuation(0.0, 4.0), 1.0);
+        assert_eq!(Falloff::Linear.attenuation(2.0, 4.0), 0.5);
+        assert_eq!(Falloff::Linear.attenuation(4.0, 4.0), 0.0);
+        assert_eq!(Falloff::Quadratic.attenuation(2.0, 4.0), 0.25);
+        for falloff in [Falloff::Constant, Falloff::Linear, Falloff::Quadratic].iter() {
+            assert_eq!(falloff.attenuation(4.5, 4.0), 0.0, "{:?}", falloff);
+            assert_eq!(falloff.attenuation(0.0, 0.0), 0.0, "{:?}", falloff);
+            assert_eq!(falloff.attenuation(0.0, -1.0), 0.0, "{:?}", falloff);
+        }
+    }
+
+    #[test]
+    fn opaque_cells_are_lit_but_hide_what_is_behind_them() {
+        let mut map = LightMap::new(Point::new(7, 1));
+        let light = Light::new(Point::new(1, 0), WHITE, 10.0).with_falloff(Falloff::Constant);
+        map.compute(&[light], |point| point.x == 3);
+
+        for x in 0..=3 {
+            assert!(map.is_lit(Point::new(x, 0)), "{}", x);
+            assert_eq!(map.light(Point::new(x, 0)), Some(WHITE));
+        }
+        for x in 4..7 {
+            assert!(!map.is_lit(Point::new(x, 0)), "{}", x);
+            assert_eq!(map.light(Point::new(x, 0)), Some([0.0; 3]));
+        }
+        assert_eq!(map.light(Point::new(7, 0)), None);
+        assert!(!map.is_lit(Point::new(7, 0)));
+    }
+
+    #[test]
+    fn lights_add_up_over_the_ambient_light() {
+        let mut map = LightMap::new(Point::new(5, 5)).with_ambient([0.1, 0.1, 0.1]);
+        let red = Light::new(Point::new(0, 0), [1.0, 0.0, 0.0], 4.0).with_falloff(Falloff::Linear);
+        let blue = Light::new(Point::new(4, 0), [0.0, 0.0, 1.0], 3.0)
+            .with_falloff(Falloff::Constant)
+            .with_intensity(0.5);
+        map.compute(&[red, blue], |_| false);
+
+        assert_eq!(map.light(Point::new(2, 0)), Some([0.6, 0.1, 0.6]));
+        assert_eq!(map.light(Point::new(0, 0)), Some([1.1, 0.1, 0.1]));
+        // out of reach of both
+        assert!(!map.is_lit(Point::new(0, 4)));
+        assert_eq!(map.drawn_light(Point::new(0, 4)), [0.1, 0.1, 0.1]);
+    }
+
+    #[test]
+    fn lights_off_the_map_still_light_the_cells_they_reach() {
+        let mut map = LightMap::new(Point::new(5, 1));
+        let light = Light::new(Point::new(6, 0), WHITE, 3.0).with_falloff(Falloff::Constant);
+        map.compute(&[light], |_| false);
+
+        let lit: Vec<bool> = (0..5).map(|x| map.is_lit(Point::new(x, 0))).collect();
+        assert_eq!(lit, vec![false, false, false, true, true]);
+    }
+
+    #[test]
+    fn cells_lit_before_are_remembered_until_forgotten() {
+        let mut map = LightMap::new(Point::new(6, 1))
+            .with_ambient([0.05; 3])
+            .with_memory([0.2; 3]);
+        let light = Light::new(Point::new(0, 0), WHITE, 2.0).with_falloff(Falloff::Constant);
+        map.compute(&[light], |_| false);
+        assert_eq!(map.drawn_light(Point::new(2, 0)), [1.05; 3]);
+
+        let moved = Light {
+            position: Point::new(5, 0),
+            ..light
+        };
+        map.compute(&[moved], |_| false);
+        assert!(!map.is_lit(Point::new(2, 0)));
+        assert!(map.is_explored(Point::new(2, 0)));
+        assert_eq!(map.drawn_light(Point::new(2, 0)), [0.2; 3]);
+        assert_eq!(map.drawn_light(Point::new(5, 0)), [1.05; 3]);
+
+        map.forget();
+        assert!(!map.is_explored(Point::new(2, 0)));
+        assert_eq!(map.drawn_light(Point::new(2, 0)), [0.05; 3]);
+        // cells lit now stay lit
+        assert_eq!(map.drawn_light(Point::new(5, 0)), [1.05; 3]);
+    }
+
+    #[test]
+    fn panels_are_lit_and_cleared() {
+        let mut panel = Panel::new(Dimensions::new(1, 1, 3, 1, 0, 0));
+        let mut map = LightMap::new(Point::new(3, 1)).with_memory([0.2; 3]);
+        let light = Light::new(Point::new(0, 0), WHITE, 1.0).with_falloff(Falloff::Constant);
+        map.compute(&[light], |_| false);
+        map.apply(&mut panel);
+
+        let lights: Vec<[f32; 3]> = (0..3)
+            .map(|x| panel.get(Point::new(x, 0)).unwrap().effects.light)
+            .collect();
+        assert_eq!(lights, vec![WHITE, WHITE, [0.0; 3]]);
+
+        clear_lighting(&mut panel);
+        assert_eq!(panel.get(Point::new(2, 0)).unwrap().effects.light, WHITE);
+    }
+}
@@ -0,0 +1,376 @@
+use crate::error::OozeResult;
+use crate::geometry::{rect_outline, Point, Rect};
+use crate::terminal::Panel;
+
+// directions a border cell connects to
+const UP: u8 = 1;
+const DOWN: u8 = 2;
+const LEFT: u8 = 4;
+const RIGHT: u8 = 8;
+
+/// The sprite ids a border is made of. Tees are named after the side their stem points to.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BorderSet {
+    pub horizontal: String,
+    pub vertical: String,
+    pub top_left: String,
+    pub top_right: String,
+    pub bottom_left: String,
+    pub bottom_right: String,
+    pub tee_up: String,
+    pub tee_down: String,
+    pub tee_left: String,
+    pub tee_right: String,
+    pub cross: String,
+}
+
+impl BorderSet {
+    /// Create a BorderSet from a string of its eleven characters in field order, for example "─│┌┐└┘┴┬┤├┼".
+    /// Returns None if the string doesn't have exactly eleven characters.
+    pub fn from_chars(chars: &str) -> Option<BorderSet> {
+        let ids: Vec<String> = chars.chars().map(|c| c.to_string()).collect();
+        if ids.len() != 11 {
+            return None;
+        }
+
+        let set = BorderSet {
+            horizontal: ids[0].clone(),
+            vertical: ids[1].clone(),
+            top_left: ids[2].clone(),
+            top_right: ids[3].clone(),
+            bottom_left: ids[4].clone(),
+            bottom_right: ids[5].clone(),
+            tee_up: ids[6].clone(),
+            tee_down: ids[7].clone(),
+            tee_left: ids[8].clone(),
+            tee_right: ids[9].clone(),
+            cross: ids[10].clone(),
+        };
+
+        Some(set)
+    }
+
+    /// A BorderSet that uses the same sprite everywhere, like Panel::make_border.
+    pub fn uniform(id: &str) -> BorderSet {
+        BorderSet {
+            horizontal: id.to_string(),
+            vertical: id.to_string(),
+            top_left: id.to_string(),
+            top_right: id.to_string(),
+            bottom_left: id.to_string(),
+            bottom_right: id.to_string(),
+            tee_up: id.to_string(),
+            tee_down: id.to_string(),
+            tee_left: id.to_string(),
+            tee_right: id.to_string(),
+            cross: id.to_string(),
+        }
+    }
+
+    /// The sprite id for a cell connecting to the given directions.
+    fn sprite_for(&self, mask: u8) -> &str {
+        match mask {
+            m if m == UP | DOWN | LEFT | RIGHT => &self.cross,
+            m if m == LEFT | RIGHT | UP => &self.tee_up,
+            m if m == LEFT | RIGHT | DOWN => &self.tee_down,
+            m if m == UP | DOWN | LEFT => &self.tee_left,
+            m if m == UP | DOWN | RIGHT => &self.tee_right,
+            m if m == DOWN | RIGHT => &self.top_left,
+            m if m == DOWN | LEFT => &self.top_right,
+            m if m == UP | RIGHT => &self.bottom_left,
+            m if m == UP | LEFT => &self.bottom_right,
+            m if m & (UP | DOWN) != 0 => &self.vertical,
+            _ => &self.horizontal,
+        }
+    }
+
+    /// The directions the given sprite id connects to, if it is part of this set.
+    fn mask_of(&self, id: &str) -> Option<u8> {
+        let masks = [
+            (&self.horizontal, LEFT | RIGHT),
+            (&self.vertical, UP | DOWN),
+            (&self.top_left, DOWN | RIGHT),
+            (&self.top_right, DOWN | LEFT),
+            (&self.bottom_left, UP | RIGHT),
+            (&self.bottom_right, UP | LEFT),
+            (&self.tee_up, LEFT | RIGHT | UP),
+            (&self.tee_down, LEFT | RIGHT | DOWN),
+            (&self.tee_left, UP | DOWN | LEFT),
+            (&self.tee_right, UP | DOWN | RIGHT),
+            (&self.cross, UP | DOWN | LEFT | RIGHT),
+        ];
+        masks
+            .iter()
+            .find(|(set_id, _)| set_id.as_str() == id)
+            .map(|(_, mask)| *mask)
+    }
+}
+
+/// How a border looks. Rounded and thick borders use characters outside of CP437 that the built-in font doesn't have.
+#[derive(Clone, Debug, PartialEq)]
+pub enum BorderStyle {
+    Single,
+    Double,
+    Rounded,
+    Thick,
+    Custom(BorderSet),
+}
+
+impl BorderStyle {
+    /// The sprite ids of this style.
+    pub fn set(&self) -> BorderSet {
+        let chars = match self {
+            BorderStyle::Single => "─│┌┐└┘┴┬┤├┼",
+            BorderStyle::Double => "═║╔╗╚╝╩╦╣╠╬",
+            BorderStyle::Rounded => "─│╭╮╰╯┴┬┤├┼",
+            BorderStyle::Thick => "━┃┏┓┗┛┻┳┫┣╋",
+            BorderStyle::Custom(set) => return set.clone(),
+        };
+        BorderSet::from_chars(chars).unwrap()
+    }
+}
+
+impl Panel {
+    /// Draw a border along the edge of a Rect with the right corner and edge sprites.
+    /// Where it crosses or touches an existing border of any built-in style or the same style, the two are joined with tees and crosses.
+    pub fn draw_border(
+        &mut self,
+        rect: Rect,
+        style: &BorderStyle,
+        fg_color: [f32; 4],
+        bg_color: [f32; 4],
+    ) -> OozeResult<()> {
+        if rect.size.x == 0 || rect.size.y == 0 {
+            return Ok(());
+        }
+        let set = style.set();
+        let mut known = vec![set.clone()];
+        for built_in in &[
+            BorderStyle::Single,
+            BorderStyle::Double,
+            BorderStyle::Rounded,
+            BorderStyle::Thick,
+        ] {
+            known.push(built_in.set());
+        }
+
+        let (left, bottom) = (rect.bottom_left.x, rect.bottom_left.y);
+        let (right, top) = (left + rect.size.x - 1, bottom + rect.size.y - 1);
+        let bounds = self.rect();
+
+        for point in rect_outline(rect) {
+            if !bounds.contains_point(point) {
+                continue;
+            }
+
+            let mut mask = 0;
+            if point.y == bottom || point.y == top {
+                if point.x > left {
+                    mask |= LEFT;
+                }
+                if point.x < right {
+                    mask |= RIGHT;
+                }
+            }
+            if point.x == left || point.x == right {
+                if point.y > bottom {
+                    mask |= DOWN;
+                }
+                if point.y < top {
+                    mask |= UP;
+                }
+            }
+
+            let existing = &self.get(point)?.sprite_id;
+            if let Some(existing_mask) = known.iter().find_map(|s| s.mask_of(existing)) {
+                mask |= existing_mask;
+            }
+
+            let id = set.sprite_for(mask).to_string();
+            self.place(point.x, point.y, &id, fg_color, bg_color)?;
+        }
+
+        Ok(())
+    }
+
+    /// Draw a border around the edge of this Panel, see Panel::draw_border.
+    pub fn make_box_border(
+        &mut self,
+        style: &BorderStyle,
+        fg_color: [f32; 4],
+        bg_color: [f32; 4],
+    ) -> OozeResult<()> {
+        self.draw_border(self.rect(), style, fg_color, bg_color)
+    }
+
+    /// Write a title centered in the top edge of a border around rect, with a space on either side.
+    /// Titles too long for the edge are cut off, the corners are always kept.
+    pub fn draw_border_title(
+        &mut self,
+        rect: Rect,
+        title: &str,
+        fg_color: [f32; 4],
+        bg_color: [f32; 4],
+    ) -> OozeResult<()> {
+        if rect.size.y == 0 {
+            return Ok(());
+        }
+        let top = rect.bottom_left.y + rect.size.y - 1;
+        self.print_on_edge(rect, top, title, fg_color, bg_color)
+    }
+
+    /// Write a footer centered in the bottom edge of a border around rect, see Panel::draw_border_title.
+    pub fn draw_border_footer(
+        &mut self,
+        rect: Rect,
+        footer: &str,
+        fg_color: [f32; 4],
+        bg_color: [f32; 4],
+    ) -> OozeResult<()> {
+        let bottom = rect.bottom_left.y;
+        self.print_on_edge(rect, bottom, footer, fg_color, bg_color)
+    }
+
+    fn print_on_edge(
+        &mut self,
+        rect: Rect,
+        y: u32,
+        text: &str,
+        fg_color: [f32; 4],
+        bg_color: [f32; 4],
+    ) -> OozeResult<()> {
+        if rect.size.x < 3 || text.is_empty() {
+            return Ok(());
+        }
+        let room = (rect.size.x - 2) as usize;
+        let padded = format!(" {} ", text);
+        let shown: String = padded.chars().take(room).collect();
+        let length = shown.chars().count() as u32;
+        let x = rect.bottom_left.x + 1 + (room as u32 - length) / 2;
+
+        let bounds = self.rect();
+        for (i, c) in shown.chars().enumerate() {
+            let point = Point::new(x + i as u32, y);
+            if bounds.contains_point(point) {
+                self.place(point.x, point.y, &c.to_string(), fg_color, bg_color)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::Dimensions;
+
+    const WHITE: [f32; 4] = [1.0, 1.0, 1.0, 1.0];
+    const BLACK: [f32; 4] = [0.0, 0.0, 0.0, 1.0];
+
+    fn panel(width: u32, height: u32) -> Panel {
+        let mut panel = Panel::new(Dimensions::new(1, 1, width, height, 0, 0));
+        panel
+            .make_box_border(&BorderStyle::Single, WHITE, BLACK)
+            .unwrap();
+        panel
+    }
+
+    fn rect(x: u32, y: u32, width: u32, height: u32) -> Rect {
+        Rect::new(Point::new(x, y), Point::new(width, height))
+    }
+
+    #[test]
+    fn box_borders() {
+        assert_eq!(
+            panel(6, 4).to_plain_text(),
+            "┌────┐\n│    │\n│    │\n└────┘"
+        );
+
+        let mut double = Panel::new(Dimensions::new(1, 1, 3, 2, 0, 0));
+        double
+            .make_box_border(&BorderStyle::Double, WHITE, BLACK)
+            .unwrap();
+        assert_eq!(double.to_plain_text(), "╔═╗\n╚═╝");
+    }
+
+    #[test]
+    fn touching_borders_are_joined() {
+        let mut panel = panel(7, 5);
+        panel
+            .draw_border(rect(3, 0, 4, 5), &BorderStyle::Single, WHITE, BLACK)
+            .unwrap();
+
+        assert_eq!(
+            panel.to_plain_text(),
+            "┌──┬──┐\n│  │  │\n│  │  │\n│  │  │\n└──┴──┘"
+        );
+    }
+
+    #[test]
+    fn crossing_borders_are_joined() {
+        let mut panel = panel(5, 5);
+        panel
+            .draw_border(rect(0, 2, 5, 1), &BorderStyle::Single, WHITE, BLACK)
+            .unwrap();
+        panel
+            .draw_border(rect(2, 0, 1, 5), &BorderStyle::Single, WHITE, BLACK)
+            .unwrap();
+
+        assert_eq!(panel.to_plain_text(), "┌─┬─┐\n│ │ │\n├─┼─┤\n│ │ │\n└─┴─┘");
+    }
+
+    #[test]
+    fn other_styles_are_joined_in_the_new_style() {
+        let mut panel = panel(5, 3);
+        panel
+            .draw_border(rect(0, 1, 5, 1), &BorderStyle::Double, WHITE, BLACK)
+            .unwrap();
+
+        assert_eq!(panel.to_plain_text(), "┌───┐\n╠═══╣\n└───┘");
+    }
+
+    #[test]
+    fn empty_rects_draw_nothing() {
+        let mut panel = panel(4, 3);
+        let before = panel.to_plain_text();
+        panel
+            .draw_border(rect(1, 1, 0, 2), &BorderStyle::Double, WHITE, BLACK)
+            .unwrap();
+
+        assert_eq!(panel.to_plain_text(), before);
+    }
+
+    #[test]
+    fn titles_and_footers_are_centered() {
+        let mut panel = panel(10, 3);
+        let area = panel.rect();
+        panel.draw_border_title(area, "Hi", WHITE, BLACK).unwrap();
+        panel.draw_border_footer(area, "abc", WHITE, BLACK).unwrap();
+
+        assert_eq!(panel.to_plain_text(), "┌── Hi ──┐\n│        │\n└─ abc ──┘");
+    }
+
+    #[test]
+    fn long_titles_are_cut_off_inside_the_corners() {
+        let mut short = panel(8, 2);
+        let area = short.rect();
+        short
+            .draw_border_title(area, "Inventory", WHITE, BLACK)
+            .unwrap();
+        short
+            .draw_border_footer(area, "Press any key", WHITE, BLACK)
+            .unwrap();
+        assert_eq!(short.to_plain_text(), "┌ Inven┐\n└ Press┘");
+
+        let mut narrow = panel(3, 2);
+        let area = narrow.rect();
+        narrow.draw_border_title(area, "Map", WHITE, BLACK).unwrap();
+        assert_eq!(narrow.to_plain_text(), "┌ ┐\n└─┘");
+
+        let mut tiny = panel(2, 2);
+        let area = tiny.rect();
+        tiny.draw_border_title(area, "Map", WHITE, BLACK).unwrap();
+        assert_eq!(tiny.to_plain_text(), "┌┐\n└┘");
+    }
+}
@@ -0,0 +1,311 @@
+use std::collections::VecDeque;
+
+use crate::error::OozeResult;
+use crate::geometry::{circle, ellipse, line, rect_outline, Point, Rect};
+use crate::terminal::{Glyph, Panel};
+
+/// What the drawing functions paint each cell with.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Brush {
+    pub sprite_id: String,
+    pub fg_color: [f32; 4],
+    pub bg_color: [f32; 4],
+}
+
+impl Brush {
+    /// Create a new Brush.
+    pub fn new(sprite_id: &str, fg_color: [f32; 4], bg_color: [f32; 4]) -> Brush {
+        Brush {
+            sprite_id: sprite_id.to_string(),
+            fg_color,
+            bg_color,
+        }
+    }
+
+    /// A Brush that paints Glyphs like the one given.
+    pub fn from_glyph(glyph: &Glyph) -> Brush {
+        Brush::new(&glyph.sprite_id, glyph.fg_color, glyph.bg_color)
+    }
+
+    /// The Glyph this Brush paints at the given Point.
+    pub fn glyph(&self, point: Point) -> OozeResult<Glyph> {
+        Glyph::new(point, self.fg_color, self.bg_color, self.sprite_id.clone())
+    }
+}
+
+/// Which cells a flood fill spreads to, compared to the cell it starts on.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FloodMatch {
+    /// Cells with the same sprite id.
+    SpriteId,
+    /// Cells with the same foreground and background colors.
+    Colors,
+    /// Cells with the same sprite id and colors.
+    Glyph,
+}
+
+impl FloodMatch {
+    fn matches(self, glyph: &Glyph, start: &Glyph) -> bool {
+        let same_sprite = glyph.sprite_id == start.sprite_id;
+        let same_colors = glyph.fg_color == start.fg_color && glyph.bg_color == start.bg_color;
+        match self {
+            FloodMatch::SpriteId => same_sprite,
+            FloodMatch::Colors => same_colors,
+            FloodMatch::Glyph => same_sprite && same_colors,
+        }
+    }
+}
+
+impl Panel {
+    /// Paint the given Points with a Brush, skipping the ones outside this Panel.
+    pub fn paint(&mut self, points: &[Point], brush: &Brush) -> OozeResult<()> {
+        let rect = self.rect();
+        for point in points {
+            if rect.contains_point(*point) {
+                self.set(*point, brush.glyph(*point)?)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Draw a straight line between two Points, both included.
+    pub fn draw_line(&mut self, from: Point, to: Point, brush: &Brush) -> OozeResult<()> {
+        self.paint(&line(from, to), brush)
+    }
+
+    /// Draw the outline of a Rect.
+    pub fn draw_rect(&mut self, rect: Rect, brush: &Brush) -> OozeResult<()> {
+        self.paint(&rect_outline(rect), brush)
+    }
+
+    /// Fill a Rect.
+    pub fn fill_rect(&mut self, rect: Rect, brush: &Brush) -> OozeResult<()> {
+        self.paint(&rect.points(), brush)
+    }
+
+    /// Draw a circle around center, filled or only its outline.
+    pub fn draw_circle(
+        &mut self,
+        center: Point,
+        radius: u32,
+        filled: bool,
+        brush: &Brush,
+    ) -> OozeResult<()> {
+        self.paint(&circle(center, radius, filled), brush)
+    }
+
+    /// Draw an ellipse around center with the given horizontal and vertical radius, filled or only its outline.
+    pub fn draw_ellipse(
+        &mut self,
+        center: Point,
+        radius_x: u32,
+        radius_y: u32,
+        filled: bool,
+        brush: &Brush,
+    ) -> OozeResult<()> {
+        self.paint(&ellipse(center, radius_x, radius_y, filled), brush)
+    }
+
+    /// Paint the area connected to start (not diagonally) whose cells match the start cell.
+    pub fn flood_fill(
+        &mut self,
+        start: Point,
+        matching: FloodMatch,
+        brush: &Brush,
+    ) -> OozeResult<()> {
+        let start_glyph = self.get(start)?.clone();
+        let (width, height) = (self.dims.term_size.x, self.dims.term_size.y);
+        let mut visited = vec![vec![false; height as usize]; width as usize];
+        let mut queue = VecDeque::new();
+        let mut area = Vec::new();
+
+        visited[start.x as usize][start.y as usize] = true;
+        queue.push_back(start);
+        while let Some(point) = queue.pop_front() {
+            area.push(point);
+
+            let mut neighbors = Vec::with_capacity(4);
+            if point.x > 0 {
+                neighbors.push(Point::new(point.x - 1, point.y));
+            }
+            if point.x + 1 < width {
+                neighbors.push(Point::new(point.x + 1, point.y));
+            }
+            if point.y > 0 {
+                neighbors.push(Point::new(point.x, point.y - 1));
+            }
+            if point.y + 1 < height {
+                neighbors.push(Point::new(point.x, point.y + 1));
+            }
+
+            for next in neighbors {
+                let seen = &mut visited[next.x as usize][next.y as usize];
+                if !*seen && matching.matches(self.get(next)?, &start_glyph) {
+                    *seen = true;
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        self.paint(&area, brush)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::Dimensions;
+
+    const WHITE: [f32; 4] = [1.0, 1.0, 1.0, 1.0];
+    const BLACK: [f32; 4] = [0.0, 0.0, 0.0, 1.0];
+
+    /// A 5x5 Panel of floor.
+    fn floor() -> Panel {
+        let mut panel = Panel::new(Dimensions::new(1, 1, 5, 5, 0, 0));
+        panel.fill_with("floor", WHITE, BLACK).unwrap();
+        panel
+    }
+
+    fn brush(sprite_id: &str) -> Brush {
+        Brush::new(sprite_id, WHITE, BLACK)
+    }
+
+    fn sprite_at(panel: &Panel, x: u32, y: u32) -> &str {
+        &panel.get(Point::new(x, y)).unwrap().sprite_id
+    }
+
+    fn count(panel: &Panel, sprite_id: &str) -> usize {
+        panel
+            .glyphs()
+            .iter()
+            .filter(|glyph| glyph.sprite_id == sprite_id)
+            .count()
+    }
+
+    #[test]
+    fn shapes_are_clipped_to_the_panel() {
+        let mut panel = floor();
+        panel
+            .draw_line(Point::new(0, 0), Point::new(9, 9), &brush("wall"))
+            .unwrap();
+        assert_eq!(count(&panel, "wall"), 5);
+        assert_eq!(sprite_at(&panel, 4, 4), "wall");
+
+        let mut panel = floor();
+        panel
+            .draw_circle(Point::new(4, 4), 2, true, &brush("water"))
+            .unwrap();
+        assert_eq!(sprite_at(&panel, 4, 4), "water");
+        assert_eq!(sprite_at(&panel, 0, 0), "floor");
+    }
+
+    #[test]
+    fn rects() {
+        let mut panel = floor();
+        let rect = Rect::new(Point::new(1, 1), Point::new(3, 3));
+        panel.draw_rect(rect, &brush("wall")).unwrap();
+        assert_eq!(count(&panel, "wall"), 8);
+        assert_eq!(sprite_at(&panel, 2, 2), "floor");
+
+        panel.fill_rect(rect, &brush("rug")).unwrap();
+        assert_eq!(count(&panel, "rug"), 9);
+
+        panel
+            .fill_rect(
+                Rect::new(Point::new(1, 1), Point::new(0, 4)),
+                &brush("hole"),
+            )
+            .unwrap();
+        assert_eq!(count(&panel, "hole"), 0);
+    }
+
+    #[test]
+    fn degenerate_shapes() {
+        let mut panel = floor();
+        panel
+            .draw_circle(Point::new(2, 2), 0, false, &brush("dot"))
+            .unwrap();
+        panel
+            .draw_ellipse(Point::new(0, 4), 0, 0, true, &brush("spot"))
+            .unwrap();
+        panel
+            .draw_line(Point::new(3, 0), Point::new(3, 0), &brush("pip"))
+            .unwrap();
+
+        assert_eq!(count(&panel, "dot"), 1);
+        assert_eq!(count(&panel, "spot"), 1);
+        assert_eq!(count(&panel, "pip"), 1);
+        assert_eq!(count(&panel, "floor"), 22);
+    }
+
+    #[test]
+    fn flood_fill_stops_at_walls() {
+        let mut panel = floor();
+        panel
+            .draw_line(Point::new(2, 0), Point::new(2, 4), &brush("wall"))
+            .unwrap();
+
+        panel
+            .flood_fill(Point::new(0, 0), FloodMatch::SpriteId, &brush("water"))
+            .unwrap();
+
+        assert_eq!(count(&panel, "water"), 10);
+        assert_eq!(count(&panel, "wall"), 5);
+        assert!((3..5).all(|x| (0..5).all(|y| sprite_at(&panel, x, y) == "floor")));
+    }
+
+    #[test]
+    fn flood_fill_does_not_spread_diagonally() {
+        let mut panel = floor();
+        panel
+            .paint(&[Point::new(1, 0), Point::new(0, 1)], &brush("wall"))
+            .unwrap();
+
+        panel
+            .flood_fill(Point::new(0, 0), FloodMatch::SpriteId, &brush("water"))
+            .unwrap();
+
+        assert_eq!(count(&panel, "water"), 1);
+        assert_eq!(sprite_at(&panel, 1, 1), "floor");
+    }
+
+    /// Floor with a row of red floor across the middle.
+    fn striped() -> Panel {
+        let mut panel = floor();
+        let red_floor = Brush::new("floor", [1.0, 0.0, 0.0, 1.0], BLACK);
+        panel
+            .draw_line(Point::new(0, 2), Point::new(4, 2), &red_floor)
+            .unwrap();
+        panel
+    }
+
+    #[test]
+    fn flood_fill_matching() {
+        // the red row has the same sprite, so a sprite match crosses it
+        let mut by_sprite = striped();
+        by_sprite
+            .flood_fill(Point::new(0, 0), FloodMatch::SpriteId, &brush("water"))
+            .unwrap();
+        assert_eq!(count(&by_sprite, "water"), 25);
+
+        // but its colors stop a color or glyph match
+        for &matching in &[FloodMatch::Colors, FloodMatch::Glyph] {
+            let mut filled = striped();
+            filled
+                .flood_fill(Point::new(0, 0), matching, &brush("water"))
+                .unwrap();
+            assert_eq!(count(&filled, "water"), 10);
+            assert_eq!(sprite_at(&filled, 0, 4), "floor");
+        }
+    }
+
+    #[test]
+    fn flood_fill_outside_the_panel_is_an_error() {
+        let mut panel = floor();
+        assert!(panel
+            .flood_fill(Point::new(5, 0), FloodMatch::Glyph, &brush("water"))
+            .is_err());
+        assert_eq!(count(&panel, "floor"), 25);
+    }
+}
@@ -0,0 +1,242 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::geometry::{cell_to_screen, Dimensions, Point};
+use crate::graphics::Vertex;
+use crate::terminal::{Glyph, Terminal};
+
+static NEXT_FLOATING_SPRITE_ID: AtomicU64 = AtomicU64::new(0);
+
+/// A handle to a FloatingSprite on a Terminal.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct FloatingSpriteId(u64);
+
+impl FloatingSpriteId {
+    /// A FloatingSpriteId no other FloatingSprite has.
+    pub fn next() -> FloatingSpriteId {
+        FloatingSpriteId(NEXT_FLOATING_SPRITE_ID.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+/// A sprite that isn't tied to a cell, for projectiles, smoothly moving entities and particles.
+/// Positions are in cells with the origin at the bottom-left like Points, but fractional.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FloatingSprite {
+    pub sprite_id: String,
+    /// Where the center of the sprite is. A sprite centered on [x + 0.5, y + 0.5] covers the cell (x, y) exactly.
+    pub position: [f32; 2],
+    /// Counter-clockwise rotation around the center, in radians.
+    pub rotation: f32,
+    /// Width and height, in cells.
+    pub scale: [f32; 2],
+    /// Multiplies the colors of the sprite, like a Glyph's foreground color.
+    pub tint: [f32; 4],
+    /// Fills the transparent parts of the sprite, like a Glyph's background color.
+    pub bg_color: [f32; 4],
+    /// Drawn above the Panels of this layer and below the ones of the next.
+    pub layer: usize,
+    /// Order among FloatingSprites of the same layer, higher is drawn on top.
+    pub z_index: i32,
+    pub hidden: bool,
+
+    id: FloatingSpriteId,
+}
+
+impl FloatingSprite {
+    /// Create a new one cell sized, untinted FloatingSprite centered on position, on layer 0.
+    pub fn new(sprite_id: &str, position: [f32; 2]) -> FloatingSprite {
+        FloatingSprite {
+            sprite_id: sprite_id.to_string(),
+            position,
+            rotation: 0.0,
+            scale: [1.0, 1.0],
+            tint: [1.0, 1.0, 1.0, 1.0],
+            bg_color: [0.0, 0.0, 0.0, 0.0],
+            layer: 0,
+            z_index: 0,
+            hidden: false,
+            id: FloatingSpriteId::next(),
+        }
+    }
+
+    /// Create a new FloatingSprite covering a cell, see FloatingSprite::new.
+    pub fn at_cell(sprite_id: &str, cell: Point) -> FloatingSprite {
+        FloatingSprite::new(sprite_id, [cell.x as f32 + 0.5, cell.y as f32 + 0.5])
+    }
+
+    /// Returns a copy of this FloatingSprite with the given rotation.
+    pub fn with_rotation(self, rotation: f32) -> FloatingSprite {
+        let mut new = self;
+        new.rotation = rotation;
+        new
+    }
+
+    /// Returns a copy of this FloatingSprite with the given size in cells.
+    pub fn with_scale(self, scale: [f32; 2]) -> FloatingSprite {
+        let mut new = self;
+        new.scale = scale;
+        new
+    }
+
+    /// Returns a copy of this FloatingSprite with the given tint.
+    pub fn with_tint(self, tint: [f32; 4]) -> FloatingSprite {
+        let mut new = self;
+        new.tint = tint;
+        new
+    }
+
+    /// Returns a copy of this FloatingSprite with the given background color.
+    pub fn with_bg_color(self, bg_color: [f32; 4]) -> FloatingSprite {
+        let mut new = self;
+        new.bg_color = bg_color;
+        new
+    }
+
+    /// Returns a copy of this FloatingSprite on the given layer.
+    pub fn with_layer(self, layer: usize) -> FloatingSprite {
+        let mut new = self;
+        new.layer = layer;
+        new
+    }
+
+    /// Returns a copy of this FloatingSprite with the given z_index.
+    pub fn with_z_index(self, z_index: i32) -> FloatingSprite {
+        let mut new = self;
+        new.z_index = z_index;
+        new
+    }
+
+    /// The id of this FloatingSprite.
+    pub fn id(&self) -> FloatingSpriteId {
+        self.id
+    }
+
+    /// The cell under the center of this FloatingSprite, or None if it is left of or below the terminal.
+    pub fn cell(&self) -> Option<Point> {
+        if self.position[0] < 0.0 || self.position[1] < 0.0 {
+            return None;
+        }
+        Some(Point::new(self.position[0] as u32, self.position[1] as u32))
+    }
+
+    /// The corners of this FloatingSprite after scaling and rotation, in cells, as [top-left, top-right, bottom-left, bottom-right].
+    pub fn corners(&self) -> [[f32; 2]; 4] {
+        let (half_width, half_height) = (self.scale[0] / 2.0, self.scale[1] / 2.0);
+        let (sin, cos) = self.rotation.sin_cos();
+        let corner = |x: f32, y: f32| {
+            [
+                self.position[0] + x * cos - y * sin,
+                self.position[1] + x * sin + y * cos,
+            ]
+        };
+        [
+            corner(-half_width, half_height),
+            corner(half_width, half_height),
+            corner(-half_width, -half_height),
+            corner(half_width, -half_height),
+        ]
+    }
+
+    /// Calculate the vertices for this FloatingSprite's quad on the screen, in the same order as Point::screen_verts.
+    pub fn screen_verts(&self, terminal_dims: Dimensions) -> [Vertex; 4] {
+        let [top_left, top_right, bottom_left, bottom_right] = self.corners();
+        [
+            Vertex::from_arrays(
+                cell_to_screen(top_left[0], top_left[1], terminal_dims),
+                [0.0, 1.0],
+            ),
+            Vertex::from_arrays(
+                cell_to_screen(top_right[0], top_right[1], terminal_dims),
+                [1.0, 1.0],
+            ),
+            Vertex::from_arrays(
+                cell_to_screen(bottom_left[0], bottom_left[1], terminal_dims),
+                [0.0, 0.0],
+            ),
+            Vertex::from_arrays(
+                cell_to_screen(bottom_right[0], bottom_right[1], terminal_dims),
+                [1.0, 0.0],
+            ),
+        ]
+    }
+
+    /// Where a point in cells falls on the unrotated sprite, from [0.0, 0.0] at its bottom-left to [1.0, 1.0] at its top-right.
+    /// Points outside the sprite are outside that range.
+    pub fn sprite_coords(&self, point: [f32; 2]) -> [f32; 2] {
+        let (x, y) = (point[0] - self.position[0], point[1] - self.position[1]);
+        let (sin, cos) = self.rotation.sin_cos();
+        [
+            (x * cos + y * sin) / self.scale[0] + 0.5,
+            (y * cos - x * sin) / self.scale[1] + 0.5,
+        ]
+    }
+}
+
+/// Something a Terminal draws: a Glyph at its final cell, or a FloatingSprite.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Drawable<'a> {
+    Glyph(&'a Glyph, Point),
+    Sprite(&'a FloatingSprite),
+}
+
+impl Terminal {
+    /// Add a FloatingSprite to this Terminal, returns its id.
+    pub fn add_floating_sprite(&mut self, sprite: FloatingSprite) -> FloatingSpriteId {
+        let id = sprite.id;
+        self.floating_sprites.push(sprite);
+        id
+    }
+
+    /// Get the FloatingSprite with the given id.
+    pub fn floating_sprite(&self, id: FloatingSpriteId) -> Option<&FloatingSprite> {
+        self.floating_sprites.iter().find(|sprite| sprite.id == id)
+    }
+
+    /// Get the FloatingSprite with the given id mutably, to move or change it.
+    pub fn floating_sprite_mut(&mut self, id: FloatingSpriteId) -> Option<&mut FloatingSprite> {
+        self.floating_sprites
+            .iter_mut()
+            .find(|sprite| sprite.id == id)
+    }
+
+    /// Take the FloatingSprite with the given id off this Terminal.
+    pub fn remove_floating_sprite(&mut self, id: FloatingSpriteId) -> Option<FloatingSprite> {
+        let index = self
+            .floating_sprites
+            .iter()
+            .position(|sprite| sprite.id == id)?;
+        Some(self.floating_sprites.remove(index))
+    }
+
+    /// Everything this Terminal draws, in drawing order: the glyphs of the Panel tree with each layer's FloatingSprites after it,
+    /// then the glyphs of open dialogs and the tooltip, which cover all FloatingSprites.
+    pub fn drawables(&self) -> Vec<Drawable<'_>> {
+        let mut sprites: Vec<&FloatingSprite> = self
+            .floating_sprites
+            .iter()
+            .filter(|sprite| !sprite.hidden)
+            .collect();
+        sprites.sort_by_key(|sprite| (sprite.layer, sprite.z_index));
+        let mut sprites = sprites.into_iter().peekable();
+
+        let mut result = Vec::new();
+        for (glyph, point, layer) in self.root_panel.collect_drawable_glyphs() {
+            while let Some(sprite) = sprites.next_if(|sprite| sprite.layer < layer) {
+                result.push(Drawable::Sprite(sprite));
+            }
+            result.push(Drawable::Glyph(glyph, point));
+        }
+        result.extend(sprites.map(Drawable::Sprite));
+
+        let overlays = self
+            .modals
+            .iter()
+            .map(|modal| &modal.panel)
+            .chain(self.tooltip.as_ref());
+        for panel in overlays {
+            for (glyph, point, _layer) in panel.collect_drawable_glyphs() {
+                result.push(Drawable::Glyph(glyph, point));
+            }
+        }
+        result
+    }
+}
@@ -1,14 +1,128 @@
+use serde::{Deserialize, Serialize};
+
 use crate::error::{OozeError, OozeResult};
-use crate::geometry::Point;
+use crate::geometry::{cell_to_screen, Dimensions, Point};
+use crate::graphics::Vertex;
 
 /// A glyph (letter, symbol, tile, etc.) on the screen that contains a location in terminal space, foreground and background colors, and an id to access a sprite.
-#[derive(Clone, Debug, PartialEq)]
+// struct fields go last so this serializes to toml, which needs plain values before tables
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Glyph {
-    pub location: Point,
-
     pub fg_color: [f32; 4],
     pub bg_color: [f32; 4],
     pub sprite_id: String,
+
+    pub location: Point,
+    /// How the sprite is flipped, turned and stretched. Text outputs ignore it.
+    #[serde(default)]
+    pub transform: GlyphTransform,
+    /// How the Glyph is changed when drawn, set by animations. Not saved.
+    #[serde(skip)]
+    pub effects: GlyphEffects,
+}
+
+/// Changes to how a Glyph is drawn that don't touch its colors or location, like an Animator or a LightMap makes.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GlyphEffects {
+    /// Shift from the Glyph's cell, in cells. Text outputs ignore it.
+    pub offset: [f32; 2],
+    /// Multiplies the alpha of both colors.
+    pub opacity: f32,
+    /// A color both colors are blended towards, by the tint's alpha.
+    pub tint: [f32; 4],
+    /// Multiplies both colors, before the tint is blended in.
+    pub light: [f32; 3],
+}
+
+/// How a Glyph's sprite is drawn in its cell: flipped, turned in quarter turns, and spread over two cells.
+/// The sprite is flipped first, then turned. A double width Glyph also covers the cell to its right and a double height one the cell above it,
+/// the Glyphs there are drawn over it unless they are empty.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GlyphTransform {
+    pub flip_x: bool,
+    pub flip_y: bool,
+    /// Counter-clockwise quarter turns, only the remainder of dividing by 4 matters.
+    pub quarter_turns: u8,
+    pub double_width: bool,
+    pub double_height: bool,
+}
+
+impl GlyphTransform {
+    /// Returns a copy of this GlyphTransform flipped horizontally and vertically as given.
+    pub fn with_flip(self, flip_x: bool, flip_y: bool) -> GlyphTransform {
+        let mut new = self;
+        new.flip_x = flip_x;
+        new.flip_y = flip_y;
+        new
+    }
+
+    /// Returns a copy of this GlyphTransform turned the given counter-clockwise quarter turns.
+    pub fn with_quarter_turns(self, quarter_turns: u8) -> GlyphTransform {
+        let mut new = self;
+        new.quarter_turns = quarter_turns % 4;
+        new
+    }
+
+    /// Returns a copy of this GlyphTransform spread over two cells as given.
+    pub fn with_double_size(self, double_width: bool, double_height: bool) -> GlyphTransform {
+        let mut new = self;
+        new.double_width = double_width;
+        new.double_height = double_height;
+        new
+    }
+
+    /// The width and height of the Glyph in cells.
+    pub fn size(self) -> Point {
+        Point::new(
+            if self.double_width { 2 } else { 1 },
+            if self.double_height { 2 } else { 1 },
+        )
+    }
+
+    /// The point of the untransformed sprite shown at a point of the drawn Glyph, both from [0.0, 0.0] at the bottom-left to [1.0, 1.0] at the top-right.
+    pub fn source_coords(self, coords: [f32; 2]) -> [f32; 2] {
+        let [mut u, mut v] = coords;
+        // undo the turns, then the flips
+        for _ in 0..self.quarter_turns % 4 {
+            let turned = [v, 1.0 - u];
+            u = turned[0];
+            v = turned[1];
+        }
+        if self.flip_x {
+            u = 1.0 - u;
+        }
+        if self.flip_y {
+            v = 1.0 - v;
+        }
+        [u, v]
+    }
+}
+
+impl Default for GlyphEffects {
+    /// No effects: no offset, fully opaque, no tint, full light.
+    fn default() -> GlyphEffects {
+        GlyphEffects {
+            offset: [0.0, 0.0],
+            opacity: 1.0,
+            tint: [0.0, 0.0, 0.0, 0.0],
+            light: [1.0, 1.0, 1.0],
+        }
+    }
+}
+
+impl GlyphEffects {
+    /// A color with the light, tint and opacity applied.
+    pub fn apply(&self, color: [f32; 4]) -> [f32; 4] {
+        let amount = self.tint[3];
+        let mut result = color;
+        for channel in 0..3 {
+            let lit = (color[channel] * self.light[channel]).min(1.0);
+            result[channel] = lit + (self.tint[channel] - lit) * amount;
+        }
+        result[3] = color[3] * self.opacity;
+        result
+    }
 }
 
 impl Glyph {
@@ -35,21 +149,71 @@ impl Glyph {
             fg_color,
             bg_color,
             sprite_id,
+            transform: GlyphTransform::default(),
+            effects: GlyphEffects::default(),
         };
 
         Ok(glyph)
     }
 
+    /// Returns a copy of this Glyph with the given transform.
+    pub fn with_transform(self, transform: GlyphTransform) -> Glyph {
+        let mut new = self;
+        new.transform = transform;
+        new
+    }
+
+    /// The fully transparent Glyph that new Panels are filled with.
+    pub fn empty(location: Point) -> Glyph {
+        Glyph {
+            fg_color: [1.0, 1.0, 1.0, 0.0],
+            bg_color: [0.0, 0.0, 0.0, 0.0],
+            sprite_id: "empty".to_string(),
+            location,
+            transform: GlyphTransform::default(),
+            effects: GlyphEffects::default(),
+        }
+    }
+
     /// Check if the alpha value of the background color is 1.0.
     // use by drawing functions to see if this hides lower glyphs
     pub fn is_opaque(&self) -> bool {
         // clippy complains if we compare directly against 1.0
-        (self.bg_color[3] - 1.0).abs() < 0.000_001
+        (self.drawn_bg_color()[3] - 1.0).abs() < 0.000_001
     }
 
-    /// check if both foreground and background colors are fully transparent
+    /// check if both foreground and background colors are fully transparent, or the Glyph is faded out completely,
     ///  so the drawing functions can skip computing it.
     pub fn fully_transparent(&self) -> bool {
-        self.bg_color[3] == 0.0 && self.fg_color[3] == 0.0
+        (self.bg_color[3] == 0.0 && self.fg_color[3] == 0.0) || self.effects.opacity <= 0.0
+    }
+
+    /// The foreground color this Glyph is drawn with, after its effects.
+    pub fn drawn_fg_color(&self) -> [f32; 4] {
+        self.effects.apply(self.fg_color)
+    }
+
+    /// The background color this Glyph is drawn with, after its effects.
+    pub fn drawn_bg_color(&self) -> [f32; 4] {
+        self.effects.apply(self.bg_color)
+    }
+
+    /// Calculate the vertices for this Glyph's quad on the screen when it is at point, moved by its effects' offset and shaped by its transform.
+    /// Returned in the same order as Point::screen_verts.
+    pub fn screen_verts(&self, point: Point, terminal_dims: Dimensions) -> [Vertex; 4] {
+        let x = point.x as f32 + self.effects.offset[0];
+        let y = point.y as f32 + self.effects.offset[1];
+        let size = self.transform.size();
+        let (width, height) = (size.x as f32, size.y as f32);
+        let tex = |u: f32, v: f32| self.transform.source_coords([u, v]);
+        [
+            Vertex::from_arrays(cell_to_screen(x, y + height, terminal_dims), tex(0.0, 1.0)),
+            Vertex::from_arrays(
+                cell_to_screen(x + width, y + height, terminal_dims),
+                tex(1.0, 1.0),
+            ),
+            Vertex::from_arrays(cell_to_screen(x, y, terminal_dims), tex(0.0, 0.0)),
+            Vertex::from_arrays(cell_to_screen(x + width, y, terminal_dims), tex(1.0, 0.0)),
+        ]
     }
 }
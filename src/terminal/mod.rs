@@ -1,5 +1,15 @@
+mod border;
+mod draw;
+mod floating;
 mod glyph;
 mod panel;
+mod save;
+mod tree;
 
+pub use border::*;
+pub use draw::*;
+pub use floating::*;
 pub use glyph::*;
 pub use panel::*;
+pub use save::*;
+pub use tree::*;
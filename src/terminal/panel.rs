@@ -1,17 +1,22 @@
 use crate::error::{OozeError, OozeResult};
-use crate::geometry::{Dimensions, Point, Rect};
-use crate::graphics::SpriteMap;
-use crate::terminal::Glyph;
+use crate::geometry::{Dimensions, Layout, Point, Rect};
+use crate::graphics::{SpriteMap, TexturedUniforms, UniformData, UniformSet};
+use crate::terminal::{Drawable, FloatingSprite, Glyph, GlyphTransform, PanelId};
+use crate::ui::Modal;
 use glium::backend::glutin::Display;
-use glium::uniforms::MagnifySamplerFilter::Nearest;
-use glium::uniforms::Sampler;
-use glium::{Blend, Frame, Program, Surface};
+use glium::{Blend, Program, Surface};
 
 /// The root object representing what is drawn to the screen.
 pub struct Terminal {
     pub dims: Dimensions,
 
     pub root_panel: Panel,
+    /// Sprites drawn at fractional positions between the Panels, see Terminal::drawables. Not saved with the Terminal.
+    pub floating_sprites: Vec<FloatingSprite>,
+    /// Open dialogs, drawn above the Panel tree with the last one on top. Not saved with the Terminal.
+    pub modals: Vec<Modal>,
+    /// The tooltip being shown, drawn above everything else. Not saved with the Terminal.
+    pub tooltip: Option<Panel>,
 }
 
 impl Terminal {
@@ -20,38 +25,58 @@ impl Terminal {
         Terminal {
             dims,
             root_panel: Panel::new(dims),
+            floating_sprites: Vec::new(),
+            modals: Vec::new(),
+            tooltip: None,
         }
     }
 
-    /// Collects the glyphs from alll this terminal's sub-panels and draws them to the screen ordered by layer.
-    pub fn draw(
+    /// Collects the glyphs from alll this terminal's sub-panels and its FloatingSprites and draws them to the target ordered by layer.
+    /// Each glyph is drawn with its colors and sprite texture, plus the given user uniforms.
+    pub fn draw<S: Surface>(
         &self,
-        target: &mut Frame,
+        target: &mut S,
         display: &Display,
         program: &Program,
         sprites: &SpriteMap,
+        uniforms: &UniformSet,
     ) -> OozeResult<()> {
-        let glyph_tuples = self.collect_drawable_glyphs();
-
         let params = glium::DrawParameters {
             blend: Blend::alpha_blending(),
             ..Default::default()
         };
 
-        for (glyph, point, _layer) in glyph_tuples {
-            let texture = &sprites.get(&glyph.sprite_id)?.texture;
-
-            let uniforms = glium::uniform! {
-                bg_color: glyph.bg_color,
-                fg_color: glyph.fg_color,
-                tex: Sampler::new(texture).magnify_filter(Nearest)
+        for drawable in self.drawables() {
+            let (sprite_id, fg_color, bg_color, verts) = match drawable {
+                Drawable::Glyph(glyph, point) => (
+                    &glyph.sprite_id,
+                    glyph.drawn_fg_color(),
+                    glyph.drawn_bg_color(),
+                    glyph.screen_verts(point, self.dims),
+                ),
+                Drawable::Sprite(sprite) => (
+                    &sprite.sprite_id,
+                    sprite.tint,
+                    sprite.bg_color,
+                    sprite.screen_verts(self.dims),
+                ),
+            };
+            let texture = &sprites.get(sprite_id)?.texture;
+
+            let glyph_uniforms = TexturedUniforms {
+                texture,
+                values: &[
+                    ("bg_color", UniformData::Vec4(bg_color)),
+                    ("fg_color", UniformData::Vec4(fg_color)),
+                ],
+                user: uniforms,
             };
 
             target.draw(
-                &glium::VertexBuffer::new(display, &point.screen_verts(self.dims))?,
+                &glium::VertexBuffer::new(display, &verts)?,
                 glium::index::NoIndices(glium::index::PrimitiveType::TriangleStrip),
                 program,
-                &uniforms,
+                &glyph_uniforms,
                 &params,
             )?;
         }
@@ -59,34 +84,75 @@ impl Terminal {
         Ok(())
     }
 
-    /// Collects a Vector of (Glyph, final_point, layer) from each sub-panel.
-    fn collect_drawable_glyphs(&self) -> Vec<(&Glyph, Point, usize)> {
-        let mut result: Vec<(&Glyph, Point, usize)> = Vec::new();
-        let panels: Vec<&Panel> = self.root_panel.all_sub_panels();
-        for panel in panels {
-            if !panel.hidden {
-                for glyph in panel.glyphs() {
-                    if !glyph.fully_transparent() {
-                        result.push((&glyph, glyph.location.plus(panel.dims.offset), panel.layer));
-                    }
-                }
-            }
+    /// Collects a Vector of (Glyph, final_point, layer) from each sub-panel, then each open dialog and the tooltip, in drawing order.
+    pub fn collect_drawable_glyphs(&self) -> Vec<(&Glyph, Point, usize)> {
+        let mut result = self.root_panel.collect_drawable_glyphs();
+        for modal in &self.modals {
+            result.extend(modal.panel.collect_drawable_glyphs());
+        }
+        if let Some(tooltip) = &self.tooltip {
+            result.extend(tooltip.collect_drawable_glyphs());
         }
-        result.sort_by_key(|(_, _, layer)| *layer);
         result
     }
+
+    /// The highest layer of any Panel in the tree or open dialog.
+    pub fn top_layer(&self) -> usize {
+        self.root_panel
+            .all_sub_panels()
+            .iter()
+            .map(|panel| panel.layer)
+            .chain(self.modals.iter().map(|modal| modal.panel.layer))
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Find the Panel drawn on top at a point of this Terminal and the cell of that Panel under it, see Panel::panel_at.
+    /// Open dialogs cover the Panel tree, the tooltip is never hit.
+    pub fn panel_at(&self, point: Point) -> Option<(&Panel, Point)> {
+        self.modals
+            .iter()
+            .rev()
+            .find_map(|modal| modal.panel.panel_at(point))
+            .or_else(|| self.root_panel.panel_at(point))
+    }
+
+    /// Change the size of this Terminal and its root Panel, then lay out the Panel tree again and refit any open dialogs.
+    pub fn resize(&mut self, term_size: Point) {
+        self.dims.term_size = term_size;
+        self.root_panel.resize(term_size);
+        self.root_panel.apply_layout();
+
+        for modal in &mut self.modals {
+            let layer = modal.panel.layer;
+            // a dialog is drawn on a Panel made to fit it, so this can't go out of bounds
+            if let Ok(refitted) = Modal::new(modal.dialog.clone(), self.dims) {
+                *modal = refitted;
+                modal.panel.set_layer(layer);
+            }
+        }
+    }
 }
 
 /// A sort of "sub terminal" that contains glyphs for drawing to the screen. Can contain sub-panels.
 pub struct Panel {
     pub dims: Dimensions,
+    /// Used to look the Panel up in the tree with Panel::panel and Terminal::panel.
+    pub name: Option<String>,
 
+    /// Panels on higher layers are drawn over lower ones anywhere in the tree.
     pub layer: usize,
+    /// Orders sub-panels of the same parent on the same layer, higher ones are drawn over lower ones.
+    pub z_index: i32,
+    /// Hidden Panels and all their sub-panels aren't drawn.
     pub hidden: bool,
 
     pub contents: Vec<Vec<Glyph>>,
 
     pub sub_panels: Vec<Panel>,
+    pub layout: Option<Layout>,
+
+    id: PanelId,
 }
 
 impl Panel {
@@ -94,7 +160,9 @@ impl Panel {
     pub fn new(dims: Dimensions) -> Panel {
         Panel {
             dims,
+            name: None,
             layer: 0,
+            z_index: 0,
             hidden: false,
             // 2D [x][y] Vec with capacity of [width][height]
             contents: {
@@ -102,55 +170,71 @@ impl Panel {
                 for x in 0..dims.term_size.x {
                     outer.push(Vec::with_capacity(dims.term_size.y as usize));
                     for y in 0..dims.term_size.y {
-                        outer[x as usize].push(
-                            Glyph::new(
-                                Point::new(x, y),
-                                [1.0, 1.0, 1.0, 0.0],
-                                [0.0, 0.0, 0.0, 0.0],
-                                "empty".to_string(),
-                            )
-                            .unwrap(),
-                        );
+                        outer[x as usize].push(Glyph::empty(Point::new(x, y)));
                     }
                 }
                 outer
             },
             sub_panels: Vec::new(),
+            layout: None,
+            id: PanelId::next(),
         }
     }
 
-    /// Drawing functions should check Panel.hidden before drawing.
+    /// Stop drawing this Panel and its sub-panels.
     pub fn hide(&mut self) {
         if !self.hidden {
             self.hidden = true;
         }
     }
 
-    /// Drawing functions should check Panel.hidden before drawing.
+    /// Draw this Panel again, and its sub-panels that aren't hidden themselves.
     pub fn show(&mut self) {
         if self.hidden {
             self.hidden = false;
         }
     }
 
+    /// The handle of this Panel, see Panel::panel_by_id.
+    pub fn id(&self) -> PanelId {
+        self.id
+    }
+
+    /// Set the name this Panel can be looked up by.
+    pub fn set_name(&mut self, name: &str) {
+        self.name = Some(name.to_string());
+    }
+
     /// A Rect the size of this Panel, bottom-left of Rect at (0, 0).
     pub fn rect(&self) -> Rect {
         Rect::of_size(self.dims.term_size)
     }
 
-    /// Add the given Panel as a sub-panel to this one.
-    /// Updates the given Panel's offset and sets its layer to this Panel's layer + 1.
-    pub fn add_sub_panel(&mut self, mut panel: Panel) -> OozeResult<()> {
+    /// Add the given Panel as a sub-panel to this one and return its id.
+    /// Updates the given Panel's offset, moving its own sub-panels along. If the given Panel is on a lower layer than this one,
+    /// it and its sub-panels are raised so it is on this Panel's layer. Sub-panels are drawn over their parent on the same layer.
+    pub fn add_sub_panel(&mut self, mut panel: Panel) -> OozeResult<PanelId> {
         if !self.rect().contains_rect(panel.dims.rect()) {
             return Err(Box::new(OozeError::OutOfBoundsError));
         }
 
-        panel.dims.offset = panel.dims.offset.plus(self.dims.offset);
-        panel.layer = self.layer + 1;
+        panel.move_to(panel.dims.offset.plus(self.dims.offset))?;
+        if panel.layer < self.layer {
+            panel.shift_layers((self.layer - panel.layer) as i64);
+        }
+        let id = panel.id;
 
         self.sub_panels.push(panel);
 
-        Ok(())
+        Ok(id)
+    }
+
+    /// Add delta to the layer of this Panel and all its sub-panels, stopping at 0.
+    fn shift_layers(&mut self, delta: i64) {
+        self.layer = (self.layer as i64 + delta).max(0) as usize;
+        for panel in &mut self.sub_panels {
+            panel.shift_layers(delta);
+        }
     }
 
     /// Set the Glyph at the given Point.
@@ -172,18 +256,113 @@ impl Panel {
         Ok(&self.contents[point.x as usize][point.y as usize])
     }
 
-    /// Create a new sub-panel with the given dimensions and add it to this one.
-    pub fn add_sub_panel_with(&mut self, dims: Dimensions) -> OozeResult<()> {
+    /// Flip, turn or stretch the Glyph at the given Point, see GlyphTransform.
+    pub fn set_transform(&mut self, point: Point, transform: GlyphTransform) -> OozeResult<()> {
+        if !self.rect().contains_point(point) {
+            return Err(Box::new(OozeError::OutOfBoundsError));
+        }
+        self.contents[point.x as usize][point.y as usize].transform = transform;
+
+        Ok(())
+    }
+
+    /// Create a new sub-panel with the given dimensions, add it to this one and return its id.
+    pub fn add_sub_panel_with(&mut self, dims: Dimensions) -> OozeResult<PanelId> {
         let panel = Panel::new(dims);
-        self.add_sub_panel(panel)?;
+        let id = self.add_sub_panel(panel)?;
+        Ok(id)
+    }
+
+    /// Change the size of this Panel. Glyphs that still fit are kept, new cells are empty.
+    pub fn resize(&mut self, term_size: Point) {
+        let mut resized = Panel::new(self.dims.with_term_size(term_size));
+        for (x, column) in self.contents.drain(..).enumerate() {
+            for (y, glyph) in column.into_iter().enumerate() {
+                if x < term_size.x as usize && y < term_size.y as usize {
+                    resized.contents[x][y] = glyph;
+                }
+            }
+        }
+        self.contents = resized.contents;
+        self.dims.term_size = term_size;
+    }
+
+    /// Move this Panel to a new offset in the terminal, moving its sub-panels along with it.
+    /// Nothing moves if a Panel in this tree is below or left of its parent's offset.
+    pub fn move_to(&mut self, offset: Point) -> OozeResult<()> {
+        self.check_offsets()?;
+        self.shift_to(offset);
+
+        Ok(())
+    }
+
+    fn check_offsets(&self) -> OozeResult<()> {
+        for panel in &self.sub_panels {
+            panel
+                .dims
+                .offset
+                .checked_minus(self.dims.offset)
+                .ok_or(OozeError::OutOfBoundsError)?;
+            panel.check_offsets()?;
+        }
+
         Ok(())
     }
 
+    fn shift_to(&mut self, offset: Point) {
+        let old = self.dims.offset;
+        for panel in &mut self.sub_panels {
+            // checked by move_to
+            let relative = panel
+                .dims
+                .offset
+                .checked_minus(old)
+                .unwrap_or(Point::new(0, 0));
+            panel.shift_to(offset.plus(relative));
+        }
+        self.dims.offset = offset;
+    }
+
+    /// Set the Layout of this Panel's sub-panels and apply it.
+    pub fn set_layout(&mut self, layout: Layout) {
+        self.layout = Some(layout);
+        self.apply_layout();
+    }
+
+    /// Move and resize the sub-panels of this Panel to the rects its Layout gives them, and do the same for their sub-panels.
+    /// Panels without a Layout keep their sub-panels where they are, as do sub-panels past the Layout's constraints or anchors.
+    pub fn apply_layout(&mut self) {
+        let area = self.rect();
+        let current: Vec<Point> = self.sub_panels.iter().map(|p| p.dims.term_size).collect();
+        if let Some(layout) = &mut self.layout {
+            // sub-panels laid out for the first time keep the size they were made with
+            let sizes = match layout.anchored_sizes(&current) {
+                Some(sizes) => sizes.clone(),
+                None => current,
+            };
+            let rects = layout.rects(area, &sizes);
+            let offset = self.dims.offset;
+            for (panel, rect) in self.sub_panels.iter_mut().zip(rects) {
+                panel.resize(rect.size);
+                // a sub-panel with its own sub-panels out of place stays where it is
+                panel.move_to(offset.plus(rect.bottom_left)).ok();
+            }
+        }
+        for panel in &mut self.sub_panels {
+            panel.apply_layout();
+        }
+    }
+
     /// Set the drawing layer of this Panel.
     pub fn set_layer(&mut self, layer: usize) {
         self.layer = layer;
     }
 
+    /// Set the order of this Panel among its siblings on the same layer.
+    pub fn set_z_index(&mut self, z_index: i32) {
+        self.z_index = z_index;
+    }
+
     /// Place a Glyph with the given info.
     pub fn place(
         &mut self,
@@ -201,6 +380,27 @@ impl Panel {
         Ok(())
     }
 
+    /// Place a line of text starting at the given position, one Glyph per character with the character as its sprite id.
+    /// This matches the sprite ids of the built-in font sheet. Text running past the right edge is cut off.
+    pub fn print(
+        &mut self,
+        x: u32,
+        y: u32,
+        text: &str,
+        fg_color: [f32; 4],
+        bg_color: [f32; 4],
+    ) -> OozeResult<()> {
+        for (i, c) in text.chars().enumerate() {
+            let point = Point::new(x + i as u32, y);
+            if !self.rect().contains_point(point) {
+                break;
+            }
+            self.place(point.x, point.y, &c.to_string(), fg_color, bg_color)?;
+        }
+
+        Ok(())
+    }
+
     /// Make a border of Glyphs with the given info on this Panel.
     pub fn make_border(
         &mut self,
@@ -247,6 +447,53 @@ impl Panel {
         result
     }
 
+    /// Collects a Vector of (Glyph, final_point, layer) from this Panel and its sub-panels, in drawing order.
+    pub fn collect_drawable_glyphs(&self) -> Vec<(&Glyph, Point, usize)> {
+        let mut result: Vec<(&Glyph, Point, usize)> = Vec::new();
+        for panel in self.visible_panels() {
+            for glyph in panel.glyphs() {
+                if !glyph.fully_transparent() {
+                    result.push((&glyph, glyph.location.plus(panel.dims.offset), panel.layer));
+                }
+            }
+        }
+        result
+    }
+
+    /// Returns the Panels of this tree that aren't hidden or inside a hidden Panel, in drawing order:
+    /// by layer, then parents before their sub-panels, then siblings by z_index, then siblings in the order they were added.
+    pub fn visible_panels(&self) -> Vec<&Panel> {
+        let mut result = Vec::new();
+        self.collect_visible(&mut result);
+        // a stable sort, so the tree order is kept within each layer
+        result.sort_by_key(|panel| panel.layer);
+        result
+    }
+
+    fn collect_visible<'a>(&'a self, result: &mut Vec<&'a Panel>) {
+        if self.hidden {
+            return;
+        }
+        result.push(self);
+        let mut children: Vec<&Panel> = self.sub_panels.iter().collect();
+        children.sort_by_key(|panel| panel.z_index);
+        for panel in children {
+            panel.collect_visible(result);
+        }
+    }
+
+    /// Find the Panel drawn on top at a point of the terminal, among this Panel and its sub-panels, and the cell of that Panel under it.
+    /// Hidden Panels and their sub-panels are skipped, and so are Panels whose Glyph at the point is fully transparent.
+    pub fn panel_at(&self, point: Point) -> Option<(&Panel, Point)> {
+        self.visible_panels().into_iter().rev().find_map(|panel| {
+            let cell = point.checked_minus(panel.dims.offset)?;
+            match panel.get(cell) {
+                Ok(glyph) if !glyph.fully_transparent() => Some((panel, cell)),
+                _ => None,
+            }
+        })
+    }
+
     /// Returns a Vector of references to all the Panels below this one in the Panel tree.
     pub fn all_sub_panels(&self) -> Vec<&Panel> {
         let mut result: Vec<&Panel> = Vec::new();
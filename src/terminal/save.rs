@@ -0,0 +1,305 @@
+use bincode;
+use serde::de::{DeserializeOwned, Error as DeError};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json;
+use std::fs;
+use std::path::Path;
+use toml;
+
+use crate::error::OozeResult;
+use crate::geometry::{Dimensions, Layout};
+use crate::terminal::{Glyph, Panel, Terminal};
+
+/// The file formats Panels and Terminals can be saved in.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SaveFormat {
+    Toml,
+    Json,
+    Binary,
+}
+
+impl SaveFormat {
+    /// Pick the format from a file's extension: .toml, .json, anything else is binary.
+    pub fn from_path(path: &Path) -> SaveFormat {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("toml") => SaveFormat::Toml,
+            Some("json") => SaveFormat::Json,
+            _ => SaveFormat::Binary,
+        }
+    }
+
+    /// Serialize a value in this format.
+    pub fn to_bytes<T: Serialize>(self, value: &T) -> OozeResult<Vec<u8>> {
+        let bytes = match self {
+            SaveFormat::Toml => toml::to_string(value)?.into_bytes(),
+            SaveFormat::Json => serde_json::to_vec_pretty(value)?,
+            SaveFormat::Binary => bincode::serialize(value)?,
+        };
+
+        Ok(bytes)
+    }
+
+    /// Deserialize a value from this format.
+    pub fn from_bytes<T: DeserializeOwned>(self, bytes: &[u8]) -> OozeResult<T> {
+        let value = match self {
+            SaveFormat::Toml => toml::from_slice(bytes)?,
+            SaveFormat::Json => serde_json::from_slice(bytes)?,
+            SaveFormat::Binary => bincode::deserialize(bytes)?,
+        };
+
+        Ok(value)
+    }
+}
+
+/// Save a value to a file, in the format matching the file's extension.
+pub fn save_to_file<T: Serialize>(value: &T, path: &Path) -> OozeResult<()> {
+    fs::write(path, SaveFormat::from_path(path).to_bytes(value)?)?;
+    Ok(())
+}
+
+/// Load a value from a file, in the format matching the file's extension.
+pub fn load_from_file<T: DeserializeOwned>(path: &Path) -> OozeResult<T> {
+    SaveFormat::from_path(path).from_bytes(&fs::read(path)?)
+}
+
+impl Panel {
+    /// Save this Panel and its sub-panels to a .toml, .json or binary file.
+    /// Only Glyphs that aren't empty are written, so files can also be written by hand as layouts.
+    pub fn save(&self, path: &Path) -> OozeResult<()> {
+        save_to_file(self, path)
+    }
+
+    /// Load a Panel and its sub-panels from a .toml, .json or binary file.
+    /// Sub-panel offsets in the file are positions in the terminal, not relative to their parent.
+    pub fn load(path: &Path) -> OozeResult<Panel> {
+        load_from_file(path)
+    }
+}
+
+impl Terminal {
+    /// Save this Terminal and its whole Panel tree to a .toml, .json or binary file.
+    pub fn save(&self, path: &Path) -> OozeResult<()> {
+        save_to_file(self, path)
+    }
+
+    /// Load a Terminal from a .toml, .json or binary file.
+    pub fn load(path: &Path) -> OozeResult<Terminal> {
+        load_from_file(path)
+    }
+}
+
+// Panels are stored with a flat list of their non-empty Glyphs instead of the full grid.
+// Text formats leave out empty lists, as toml needs plain values before tables;
+// binary formats can't skip fields so they use a tuple of all of them.
+
+#[derive(Serialize)]
+struct PanelRef<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name: &'a Option<String>,
+    layer: usize,
+    z_index: i32,
+    hidden: bool,
+    dims: Dimensions,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    layout: &'a Option<Layout>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    glyphs: Vec<&'a Glyph>,
+    #[serde(skip_serializing_if = "<[Panel]>::is_empty")]
+    sub_panels: &'a [Panel],
+}
+
+#[derive(Deserialize)]
+struct PanelData {
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default)]
+    layer: usize,
+    #[serde(default)]
+    z_index: i32,
+    #[serde(default)]
+    hidden: bool,
+    dims: Dimensions,
+    #[serde(default)]
+    layout: Option<Layout>,
+    #[serde(default)]
+    glyphs: Vec<Glyph>,
+    #[serde(default)]
+    sub_panels: Vec<Panel>,
+}
+
+type PanelTuple = (
+    Option<String>,
+    usize,
+    i32,
+    bool,
+    Dimensions,
+    Option<Layout>,
+    Vec<Glyph>,
+    Vec<Panel>,
+);
+
+impl Serialize for Panel {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let glyphs: Vec<&Glyph> = self
+            .glyphs()
+            .into_iter()
+            .filter(|glyph| {
+                let empty = Glyph {
+                    effects: glyph.effects,
+                    ..Glyph::empty(glyph.location)
+                };
+                **glyph != empty
+            })
+            .collect();
+
+        if serializer.is_human_readable() {
+            PanelRef {
+                name: &self.name,
+                layer: self.layer,
+                z_index: self.z_index,
+                hidden: self.hidden,
+                dims: self.dims,
+                layout: &self.layout,
+                glyphs,
+                sub_panels: &self.sub_panels,
+            }
+            .serialize(serializer)
+        } else {
+            (
+                &self.name,
+                self.layer,
+                self.z_index,
+                self.hidden,
+                self.dims,
+                &self.layout,
+                glyphs,
+                &self.sub_panels,
+            )
+                .serialize(serializer)
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Panel {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Panel, D::Error> {
+        let data = if deserializer.is_human_readable() {
+            PanelData::deserialize(deserializer)?
+        } else {
+            let (name, layer, z_index, hidden, dims, layout, glyphs, sub_panels) =
+                PanelTuple::deserialize(deserializer)?;
+            PanelData {
+                name,
+                layer,
+                z_index,
+                hidden,
+                dims,
+                layout,
+                glyphs,
+                sub_panels,
+            }
+        };
+
+        let mut panel = Panel::new(data.dims);
+        panel.name = data.name;
+        panel.layer = data.layer;
+        panel.z_index = data.z_index;
+        panel.hidden = data.hidden;
+        for glyph in data.glyphs {
+            panel
+                .set(glyph.location, glyph)
+                .map_err(|_| D::Error::custom("glyph outside of its panel"))?;
+        }
+        panel.sub_panels = data.sub_panels;
+        panel.layout = data.layout;
+
+        Ok(panel)
+    }
+}
+
+#[derive(Serialize)]
+struct TerminalRef<'a> {
+    dims: Dimensions,
+    root_panel: &'a Panel,
+}
+
+#[derive(Deserialize)]
+struct TerminalData {
+    dims: Dimensions,
+    root_panel: Panel,
+}
+
+impl Serialize for Terminal {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        TerminalRef {
+            dims: self.dims,
+            root_panel: &self.root_panel,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Terminal {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Terminal, D::Error> {
+        let data = TerminalData::deserialize(deserializer)?;
+        let mut terminal = Terminal::new(data.dims);
+        terminal.root_panel = data.root_panel;
+
+        Ok(terminal)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::Point;
+    use std::env;
+
+    fn assert_same_panel(loaded: &Panel, saved: &Panel) {
+        assert_eq!(loaded.dims, saved.dims);
+        assert_eq!(loaded.name, saved.name);
+        assert_eq!(loaded.layer, saved.layer);
+        assert_eq!(loaded.z_index, saved.z_index);
+        assert_eq!(loaded.hidden, saved.hidden);
+        assert_eq!(loaded.contents, saved.contents);
+        assert_eq!(loaded.sub_panels.len(), saved.sub_panels.len());
+        for (loaded_sub, saved_sub) in loaded.sub_panels.iter().zip(&saved.sub_panels) {
+            assert_same_panel(loaded_sub, saved_sub);
+        }
+    }
+
+    #[test]
+    fn panel_round_trip() {
+        let mut panel = Panel::new(Dimensions::new(16, 16, 6, 4, 0, 0));
+        panel.set_name("map");
+        panel
+            .place(1, 2, "wall", [0.5, 0.25, 0.0, 1.0], [0.0, 0.0, 0.0, 1.0])
+            .unwrap();
+        let mut sub_panel = Panel::new(Dimensions::new(16, 16, 2, 2, 3, 1));
+        sub_panel.set_name("ooze");
+        sub_panel.layer = 1;
+        sub_panel.z_index = -2;
+        sub_panel.hidden = true;
+        sub_panel
+            .place(0, 1, "ooze", [0.0, 1.0, 0.0, 1.0], [0.0, 0.0, 0.0, 0.0])
+            .unwrap();
+        panel.add_sub_panel(sub_panel).unwrap();
+
+        for extension in &["toml", "json", "bin"] {
+            let path = env::temp_dir().join(format!("ooze_panel_round_trip.{}", extension));
+            panel.save(&path).unwrap();
+            let loaded = Panel::load(&path).unwrap();
+            fs::remove_file(&path).unwrap();
+
+            assert_same_panel(&loaded, &panel);
+            assert_eq!(
+                loaded
+                    .panel("ooze")
+                    .unwrap()
+                    .get(Point::new(0, 1))
+                    .unwrap()
+                    .sprite_id,
+                "ooze"
+            );
+        }
+    }
+}
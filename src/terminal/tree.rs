@@ -0,0 +1,302 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::error::{OozeError, OozeResult};
+use crate::geometry::{Point, Rect};
+use crate::terminal::{Panel, Terminal};
+
+static NEXT_PANEL_ID: AtomicU64 = AtomicU64::new(0);
+
+/// A handle to a Panel that stays valid while the Panel is moved around the tree.
+/// Every Panel gets its own when it is created, including Panels loaded from a file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct PanelId(u64);
+
+impl PanelId {
+    /// A PanelId no other Panel has.
+    pub fn next() -> PanelId {
+        PanelId(NEXT_PANEL_ID.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+impl Panel {
+    /// The first Panel with the given name, searching this Panel and then its sub-panels depth first.
+    pub fn panel(&self, name: &str) -> Option<&Panel> {
+        self.find_panel(&|panel| panel.name.as_ref().map_or(false, |n| n == name))
+    }
+
+    /// The first Panel with the given name mutably, see Panel::panel.
+    pub fn panel_mut(&mut self, name: &str) -> Option<&mut Panel> {
+        self.find_panel_mut(&|panel| panel.name.as_ref().map_or(false, |n| n == name))
+    }
+
+    /// The Panel with the given id in this Panel's tree.
+    pub fn panel_by_id(&self, id: PanelId) -> Option<&Panel> {
+        self.find_panel(&|panel| panel.id() == id)
+    }
+
+    /// The Panel with the given id in this Panel's tree mutably.
+    pub fn panel_by_id_mut(&mut self, id: PanelId) -> Option<&mut Panel> {
+        self.find_panel_mut(&|panel| panel.id() == id)
+    }
+
+    /// The Panel in this Panel's tree that has the Panel with the given id as a sub-panel.
+    pub fn parent_of(&self, id: PanelId) -> Option<&Panel> {
+        self.find_panel(&|panel| panel.sub_panels.iter().any(|sub| sub.id() == id))
+    }
+
+    /// Take the Panel with the given id out of this Panel's tree, along with its sub-panels.
+    /// Its offset is made relative to its old parent again, so it can be added to another Panel with add_sub_panel.
+    /// Nothing is removed if a Panel in its tree is below or left of its parent's offset.
+    pub fn remove_panel(&mut self, id: PanelId) -> OozeResult<Panel> {
+        let parent = self
+            .find_panel_mut(&|panel| panel.sub_panels.iter().any(|sub| sub.id() == id))
+            .ok_or_else(|| not_found(id))?;
+        let index = parent
+            .sub_panels
+            .iter()
+            .position(|sub| sub.id() == id)
+            .ok_or_else(|| not_found(id))?;
+        let relative = parent.sub_panels[index]
+            .dims
+            .offset
+            .checked_minus(parent.dims.offset)
+            .ok_or(OozeError::OutOfBoundsError)?;
+
+        record_layout_sizes(parent);
+        let mut panel = parent.sub_panels.remove(index);
+        if let Err(err) = panel.move_to(relative) {
+            parent.sub_panels.insert(index, panel);
+            return Err(err);
+        }
+        if let Some(layout) = &mut parent.layout {
+            layout.remove_entry(index);
+        }
+
+        Ok(panel)
+    }
+
+    /// Move the Panel with the given id and its sub-panels to another parent in this Panel's tree.
+    /// It keeps its offset relative to its parent, and is raised to its new parent's layer if it is lower, see Panel::add_sub_panel.
+    /// Nothing changes if it wouldn't fit inside the new parent, or the new parent is inside it.
+    pub fn reparent(&mut self, id: PanelId, new_parent: PanelId) -> OozeResult<()> {
+        let relative = {
+            let parent = self.parent_of(id).ok_or_else(|| not_found(id))?;
+            let panel = parent.panel_by_id(id).ok_or_else(|| not_found(id))?;
+            if panel.panel_by_id(new_parent).is_some() {
+                return Err(Box::new(OozeError::PanelTreeError(
+                    "a Panel can't be moved inside itself".to_string(),
+                )));
+            }
+            let offset = panel
+                .dims
+                .offset
+                .checked_minus(parent.dims.offset)
+                .ok_or(OozeError::OutOfBoundsError)?;
+            Rect::new(offset, panel.dims.term_size)
+        };
+        let target = self
+            .panel_by_id(new_parent)
+            .ok_or_else(|| not_found(new_parent))?;
+        if !target.rect().contains_rect(relative) {
+            return Err(Box::new(OozeError::OutOfBoundsError));
+        }
+
+        let panel = self.remove_panel(id)?;
+        if let Some(target) = self.panel_by_id_mut(new_parent) {
+            target.add_sub_panel(panel)?;
+        }
+
+        Ok(())
+    }
+
+    /// Move the Panel with the given id to a new position among its siblings, clamped to their count.
+    /// Sub-panels later in the list are drawn over earlier ones on the same layer.
+    pub fn reorder(&mut self, id: PanelId, index: usize) -> OozeResult<()> {
+        let parent = self
+            .find_panel_mut(&|panel| panel.sub_panels.iter().any(|sub| sub.id() == id))
+            .ok_or_else(|| not_found(id))?;
+        if let Some(from) = parent.sub_panels.iter().position(|sub| sub.id() == id) {
+            record_layout_sizes(parent);
+            let panel = parent.sub_panels.remove(from);
+            let to = index.min(parent.sub_panels.len());
+            if let Some(layout) = &mut parent.layout {
+                layout.move_entry(from, to);
+            }
+            parent.sub_panels.insert(to, panel);
+        }
+
+        Ok(())
+    }
+
+    /// Draw the Panel with the given id over all its siblings on the same layer.
+    pub fn bring_to_front(&mut self, id: PanelId) -> OozeResult<()> {
+        self.restack(id, true)
+    }
+
+    /// Draw the Panel with the given id under all its siblings on the same layer.
+    pub fn send_to_back(&mut self, id: PanelId) -> OozeResult<()> {
+        self.restack(id, false)
+    }
+
+    fn restack(&mut self, id: PanelId, to_front: bool) -> OozeResult<()> {
+        let parent = self
+            .find_panel_mut(&|panel| panel.sub_panels.iter().any(|sub| sub.id() == id))
+            .ok_or_else(|| not_found(id))?;
+        let others = parent.sub_panels.iter().filter(|sub| sub.id() != id);
+        let z_index = if to_front {
+            others
+                .map(|sub| sub.z_index)
+                .max()
+                .map_or(0, |z| z.saturating_add(1))
+        } else {
+            others
+                .map(|sub| sub.z_index)
+                .min()
+                .map_or(0, |z| z.saturating_sub(1))
+        };
+        if let Some(panel) = parent.sub_panels.iter_mut().find(|sub| sub.id() == id) {
+            panel.z_index = z_index;
+        }
+
+        Ok(())
+    }
+
+    fn find_panel(&self, matches: &dyn Fn(&Panel) -> bool) -> Option<&Panel> {
+        if matches(self) {
+            return Some(self);
+        }
+        self.sub_panels
+            .iter()
+            .find_map(|sub| sub.find_panel(matches))
+    }
+
+    fn find_panel_mut(&mut self, matches: &dyn Fn(&Panel) -> bool) -> Option<&mut Panel> {
+        if matches(self) {
+            return Some(self);
+        }
+        self.sub_panels
+            .iter_mut()
+            .find_map(|sub| sub.find_panel_mut(matches))
+    }
+}
+
+/// Give every sub-panel of a Panel with an anchored layout a size in it, see Layout::anchored_sizes.
+/// Called before its sub-panels are rearranged, so their sizes can be moved along with them.
+fn record_layout_sizes(parent: &mut Panel) {
+    let current: Vec<Point> = parent
+        .sub_panels
+        .iter()
+        .map(|sub| sub.dims.term_size)
+        .collect();
+    if let Some(layout) = &mut parent.layout {
+        layout.anchored_sizes(&current);
+    }
+}
+
+fn not_found(id: PanelId) -> OozeError {
+    OozeError::PanelTreeError(format!("no sub-panel with id {:?}", id))
+}
+
+impl Terminal {
+    /// The first Panel with the given name in the Panel tree, see Panel::panel.
+    pub fn panel(&self, name: &str) -> Option<&Panel> {
+        self.root_panel.panel(name)
+    }
+
+    /// The first Panel with the given name in the Panel tree mutably.
+    pub fn panel_mut(&mut self, name: &str) -> Option<&mut Panel> {
+        self.root_panel.panel_mut(name)
+    }
+
+    /// The Panel with the given id in the Panel tree.
+    pub fn panel_by_id(&self, id: PanelId) -> Option<&Panel> {
+        self.root_panel.panel_by_id(id)
+    }
+
+    /// The Panel with the given id in the Panel tree mutably.
+    pub fn panel_by_id_mut(&mut self, id: PanelId) -> Option<&mut Panel> {
+        self.root_panel.panel_by_id_mut(id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::{Anchor, Dimensions, Layout};
+
+    // a Panel with sub-panels a, b and c anchored to the bottom left, top right and bottom right
+    fn anchored_panel(offset: Point) -> (Panel, [PanelId; 3]) {
+        let dims = Dimensions::new(8, 8, 10, 6, offset.x, offset.y);
+        let mut panel = Panel::new(dims);
+        let mut ids = [PanelId::next(); 3];
+        for (i, size) in [Point::new(2, 2), Point::new(3, 3), Point::new(4, 1)]
+            .iter()
+            .enumerate()
+        {
+            ids[i] = panel
+                .add_sub_panel_with(dims.copy_for_panel(*size, Point::new(0, 0)))
+                .unwrap();
+        }
+        panel.set_layout(Layout::anchored(vec![
+            Anchor::BottomLeft,
+            Anchor::TopRight,
+            Anchor::BottomRight,
+        ]));
+        (panel, ids)
+    }
+
+    // shrink the panel so every sub-panel is squeezed, then grow it back and lay it out again
+    fn squeeze(panel: &mut Panel) {
+        let size = panel.dims.term_size;
+        panel.resize(Point::new(1, 1));
+        panel.apply_layout();
+        panel.resize(size);
+        panel.apply_layout();
+    }
+
+    fn placed(panel: &Panel, id: PanelId) -> (Point, Point) {
+        let sub = panel.panel_by_id(id).unwrap();
+        (sub.dims.offset, sub.dims.term_size)
+    }
+
+    #[test]
+    fn remove_keeps_anchored_sizes() {
+        let (mut panel, [a, b, c]) = anchored_panel(Point::new(0, 0));
+        let removed = panel.remove_panel(b).unwrap();
+        assert_eq!(removed.dims.offset, Point::new(7, 3));
+        squeeze(&mut panel);
+
+        assert_eq!(placed(&panel, a), (Point::new(0, 0), Point::new(2, 2)));
+        assert_eq!(placed(&panel, c), (Point::new(6, 0), Point::new(4, 1)));
+    }
+
+    #[test]
+    fn reorder_keeps_anchored_sizes() {
+        let (mut panel, [a, b, c]) = anchored_panel(Point::new(0, 0));
+        panel.reorder(c, 0).unwrap();
+        assert_eq!(panel.sub_panels[0].id(), c);
+        squeeze(&mut panel);
+
+        assert_eq!(placed(&panel, a), (Point::new(0, 0), Point::new(2, 2)));
+        assert_eq!(placed(&panel, b), (Point::new(7, 3), Point::new(3, 3)));
+        assert_eq!(placed(&panel, c), (Point::new(6, 0), Point::new(4, 1)));
+    }
+
+    #[test]
+    fn reparent_keeps_anchored_sizes() {
+        let mut root = Panel::new(Dimensions::new(8, 8, 10, 12, 0, 0));
+        let (panel, [a, b, c]) = anchored_panel(Point::new(0, 0));
+        let parent = root.add_sub_panel(panel).unwrap();
+        let other = root
+            .add_sub_panel_with(Dimensions::new(8, 8, 10, 6, 0, 6))
+            .unwrap();
+        root.reparent(b, other).unwrap();
+        assert_eq!(root.parent_of(b).unwrap().id(), other);
+        assert_eq!(placed(&root, b), (Point::new(7, 9), Point::new(3, 3)));
+
+        let panel = root.panel_by_id_mut(parent).unwrap();
+        squeeze(panel);
+        assert_eq!(placed(panel, a), (Point::new(0, 0), Point::new(2, 2)));
+        assert_eq!(placed(panel, c), (Point::new(6, 0), Point::new(4, 1)));
+    }
+}
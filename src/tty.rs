@@ -0,0 +1,272 @@
+use crossterm::event::{
+    self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEvent, KeyEventKind,
+    KeyModifiers, MouseEvent, MouseEventKind,
+};
+use crossterm::style::{Color, Print, SetBackgroundColor, SetForegroundColor};
+use crossterm::{cursor, execute, queue, terminal};
+use std::io::{self, Write};
+use std::time::{Duration, Instant};
+
+use crate::animation::{Animator, ParticleSystem};
+use crate::app::GameState;
+use crate::error::OozeResult;
+use crate::formats::{sprite_char, TextGrid};
+use crate::frame::{Frame, GameCallback};
+use crate::geometry::{Dimensions, Point};
+use crate::input::{InputEvent, Key};
+use crate::terminal::Terminal;
+
+/// Runs a game in a text terminal instead of a window, drawing each Glyph as a character with 24-bit ANSI colors.
+/// Works over SSH and on machines without OpenGL. Glyph sizes are ignored, every Glyph is one character cell.
+pub struct TtyApp<G: GameState> {
+    pub terminal: Terminal,
+    pub animator: Animator,
+    pub particles: ParticleSystem,
+
+    /// Turns sprite ids into the characters shown for them.
+    pub to_char: fn(&str) -> char,
+    /// How long each pass of the main loop takes at least.
+    pub frame_delay: Duration,
+    /// The input events read since the last frame.
+    pub events: Vec<InputEvent>,
+
+    pub closed: bool,
+    pub start_time: Instant,
+
+    pub update_game_callback: GameCallback<G>,
+    pub handle_events_callback: GameCallback<G>,
+
+    last_frame: Option<TextGrid>,
+}
+
+impl<G: GameState> TtyApp<G> {
+    /// Create a new TtyApp. Only the term_size of the Dimensions matters.
+    pub fn new(dims: Dimensions) -> TtyApp<G> {
+        TtyApp {
+            terminal: Terminal::new(dims),
+            animator: Animator::new(),
+            particles: ParticleSystem::new(),
+            to_char: sprite_char,
+            frame_delay: Duration::from_millis(33),
+            events: Vec::new(),
+            closed: false,
+            start_time: Instant::now(),
+            update_game_callback: default_update_callback,
+            handle_events_callback: default_handle_events_callback,
+            last_frame: None,
+        }
+    }
+
+    /// Draw the parts of the Terminal that changed since the last frame.
+    fn draw(&mut self, out: &mut impl Write) -> OozeResult<()> {
+        let frame = TextGrid::from_terminal(&self.terminal, self.to_char);
+
+        for (row, cells) in frame.rows.iter().enumerate() {
+            for (x, cell) in cells.iter().enumerate() {
+                let unchanged = self
+                    .last_frame
+                    .as_ref()
+                    .and_then(|last| last.rows.get(row).and_then(|r| r.get(x)))
+                    .map_or(false, |last| last == cell);
+                if unchanged {
+                    continue;
+                }
+
+                let (fg, bg) = (cell.fg_color, cell.bg_color);
+                queue!(
+                    out,
+                    cursor::MoveTo(x as u16, row as u16),
+                    SetForegroundColor(Color::Rgb {
+                        r: fg[0],
+                        g: fg[1],
+                        b: fg[2]
+                    }),
+                    SetBackgroundColor(Color::Rgb {
+                        r: bg[0],
+                        g: bg[1],
+                        b: bg[2]
+                    }),
+                    Print(cell.character)
+                )?;
+            }
+        }
+        out.flush()?;
+        self.last_frame = Some(frame);
+
+        Ok(())
+    }
+
+    /// Read input until the frame delay since frame_start has passed.
+    fn read_events(&mut self, frame_start: Instant) -> OozeResult<()> {
+        self.events.clear();
+        loop {
+            let remaining = self.frame_delay.checked_sub(frame_start.elapsed());
+            let timeout = remaining.unwrap_or_else(|| Duration::from_millis(0));
+            if !event::poll(timeout)? {
+                break;
+            }
+            let term_height = self.terminal.dims.term_size.y;
+            if let Some(input) = convert_event(event::read()?, term_height) {
+                if let InputEvent::Resized(width, height) = input {
+                    self.terminal.resize(Point::new(width, height));
+                    // the terminal may have been cleared, so draw everything again
+                    self.last_frame = None;
+                }
+                self.events.push(input);
+            }
+            if remaining.is_none() {
+                break;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The Frame given to this TtyApp's callbacks.
+    fn frame(&mut self) -> Frame<'_> {
+        Frame::new(
+            &mut self.terminal,
+            &mut self.animator,
+            &mut self.particles,
+            &self.events,
+            &mut self.closed,
+        )
+    }
+
+    fn update_game(&mut self, game_state: &mut G) {
+        let callback = self.update_game_callback;
+        callback(&mut self.frame(), game_state);
+    }
+
+    fn handle_events(&mut self, game_state: &mut G) {
+        let callback = self.handle_events_callback;
+        callback(&mut self.frame(), game_state);
+    }
+
+    /// Start this TtyApp's main loop. Takes over the text terminal until the App is closed, then restores it.
+    pub fn run(&mut self, game_state: &mut G) -> OozeResult<()> {
+        let mut out = io::stdout();
+        terminal::enable_raw_mode()?;
+        execute!(
+            out,
+            terminal::EnterAlternateScreen,
+            EnableMouseCapture,
+            cursor::Hide,
+            terminal::Clear(terminal::ClearType::All)
+        )?;
+        self.last_frame = None;
+
+        let result = self.main_loop(&mut out, game_state);
+
+        execute!(
+            out,
+            cursor::Show,
+            DisableMouseCapture,
+            terminal::LeaveAlternateScreen
+        )?;
+        terminal::disable_raw_mode()?;
+
+        result
+    }
+
+    fn main_loop(&mut self, out: &mut impl Write, game_state: &mut G) -> OozeResult<()> {
+        while !self.closed {
+            let frame_start = Instant::now();
+            let time = self.start_time.elapsed().as_secs_f32();
+            self.animator.update(&mut self.terminal, time);
+            self.particles.update(&mut self.terminal, time);
+            self.draw(out)?;
+
+            self.read_events(frame_start)?;
+            self.handle_events(game_state);
+
+            self.update_game(game_state);
+        }
+
+        Ok(())
+    }
+}
+
+/// Convert a crossterm event to an InputEvent, with mouse positions as cells of a Terminal term_height rows high.
+/// Key releases and mouse buttons are left out.
+fn convert_event(event: Event, term_height: u32) -> Option<InputEvent> {
+    match event {
+        Event::Key(KeyEvent {
+            code,
+            modifiers,
+            kind,
+            ..
+        }) => {
+            if kind == KeyEventKind::Release {
+                return None;
+            }
+            let ctrl = modifiers.contains(KeyModifiers::CONTROL);
+            if ctrl && code == KeyCode::Char('c') {
+                // raw mode swallows the interrupt signal
+                return Some(InputEvent::Close);
+            }
+            let key = match code {
+                KeyCode::Char(c) => Key::Char(c),
+                KeyCode::Enter => Key::Enter,
+                KeyCode::Esc => Key::Escape,
+                KeyCode::Backspace => Key::Backspace,
+                KeyCode::Delete => Key::Delete,
+                KeyCode::Tab => Key::Tab,
+                KeyCode::BackTab => Key::BackTab,
+                KeyCode::Up => Key::Up,
+                KeyCode::Down => Key::Down,
+                KeyCode::Left => Key::Left,
+                KeyCode::Right => Key::Right,
+                KeyCode::Home => Key::Home,
+                KeyCode::End => Key::End,
+                KeyCode::PageUp => Key::PageUp,
+                KeyCode::PageDown => Key::PageDown,
+                KeyCode::F(n) => Key::F(n),
+                _ => return None,
+            };
+            Some(InputEvent::KeyPressed {
+                key,
+                ctrl,
+                alt: modifiers.contains(KeyModifiers::ALT),
+            })
+        }
+        Event::Mouse(MouseEvent {
+            kind, column, row, ..
+        }) => match kind {
+            MouseEventKind::ScrollUp => Some(InputEvent::MouseWheel(1)),
+            MouseEventKind::ScrollDown => Some(InputEvent::MouseWheel(-1)),
+            // rows count from the top, terminal cells from the bottom
+            MouseEventKind::Moved | MouseEventKind::Drag(_) if u32::from(row) < term_height => {
+                Some(InputEvent::MouseMoved(Point::new(
+                    u32::from(column),
+                    term_height - 1 - u32::from(row),
+                )))
+            }
+            _ => None,
+        },
+        Event::Resize(width, height) => {
+            Some(InputEvent::Resized(u32::from(width), u32::from(height)))
+        }
+        _ => None,
+    }
+}
+
+/// Is the default upon TtyApp creation, does nothing.
+fn default_update_callback<G: GameState>(_frame: &mut Frame, _game_state: &mut G) {}
+
+// Is the default upon TtyApp creation, closes the TtyApp on escape or ctrl-c.
+fn default_handle_events_callback<G: GameState>(frame: &mut Frame, _game_state: &mut G) {
+    let quit = frame.events.iter().any(|event| {
+        matches!(
+            event,
+            InputEvent::Close
+                | InputEvent::KeyPressed {
+                    key: Key::Escape,
+                    ..
+                }
+        )
+    });
+    if quit {
+        frame.close();
+    }
+}
@@ -0,0 +1,244 @@
+use std::any::Any;
+
+use crate::error::OozeResult;
+use crate::geometry::Rect;
+use crate::input::{InputEvent, Key};
+use crate::terminal::Panel;
+use crate::ui::{clear_rect, print_in_rect, wrap_text, Style, Widget, WidgetEvent};
+
+/// Check if an event is a press of enter or space, which activate buttons and toggle checkboxes.
+fn is_activation(event: &InputEvent) -> bool {
+    matches!(event.key(), Some(Key::Enter) | Some(Key::Char(' ')))
+}
+
+/// Text that is wrapped to fit its region. Can't be focused.
+pub struct Label {
+    pub rect: Rect,
+    pub text: String,
+    pub style: Style,
+}
+
+impl Label {
+    /// Create a new Label.
+    pub fn new(rect: Rect, text: &str) -> Label {
+        Label {
+            rect,
+            text: text.to_string(),
+            style: Style::default(),
+        }
+    }
+}
+
+impl Widget for Label {
+    fn rect(&self) -> Rect {
+        self.rect
+    }
+
+    fn set_rect(&mut self, rect: Rect) {
+        self.rect = rect;
+    }
+
+    fn draw(&self, panel: &mut Panel, _focused: bool) -> OozeResult<()> {
+        let (fg, bg) = self.style.colors(false);
+        clear_rect(panel, self.rect, bg)?;
+        for (row, line) in wrap_text(&self.text, self.rect.size.x as usize)
+            .iter()
+            .enumerate()
+        {
+            print_in_rect(panel, self.rect, row as u32, 0, line, fg, bg)?;
+        }
+
+        Ok(())
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+/// A button with centered text, activated with enter or space.
+pub struct Button {
+    pub rect: Rect,
+    pub text: String,
+    pub style: Style,
+}
+
+impl Button {
+    /// Create a new Button.
+    pub fn new(rect: Rect, text: &str) -> Button {
+        Button {
+            rect,
+            text: text.to_string(),
+            style: Style::default(),
+        }
+    }
+}
+
+impl Widget for Button {
+    fn rect(&self) -> Rect {
+        self.rect
+    }
+
+    fn set_rect(&mut self, rect: Rect) {
+        self.rect = rect;
+    }
+
+    fn draw(&self, panel: &mut Panel, focused: bool) -> OozeResult<()> {
+        let (fg, bg) = self.style.colors(focused);
+        clear_rect(panel, self.rect, bg)?;
+
+        let length = self.text.chars().count() as u32;
+        let column = self.rect.size.x.saturating_sub(length) / 2;
+        let row = self.rect.size.y.saturating_sub(1) / 2;
+        print_in_rect(panel, self.rect, row, column, &self.text, fg, bg)
+    }
+
+    fn handle_input(&mut self, event: &InputEvent) -> WidgetEvent {
+        if is_activation(event) {
+            WidgetEvent::Activated
+        } else {
+            WidgetEvent::Ignored
+        }
+    }
+
+    fn focusable(&self) -> bool {
+        true
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+/// A box that is checked or not, shown as "[x] text". Toggled with enter or space.
+pub struct Checkbox {
+    pub rect: Rect,
+    pub text: String,
+    pub checked: bool,
+    pub style: Style,
+}
+
+impl Checkbox {
+    /// Create a new Checkbox.
+    pub fn new(rect: Rect, text: &str, checked: bool) -> Checkbox {
+        Checkbox {
+            rect,
+            text: text.to_string(),
+            checked,
+            style: Style::default(),
+        }
+    }
+}
+
+impl Widget for Checkbox {
+    fn rect(&self) -> Rect {
+        self.rect
+    }
+
+    fn set_rect(&mut self, rect: Rect) {
+        self.rect = rect;
+    }
+
+    fn draw(&self, panel: &mut Panel, focused: bool) -> OozeResult<()> {
+        let (fg, bg) = self.style.colors(focused);
+        clear_rect(panel, self.rect, bg)?;
+
+        let mark = if self.checked { 'x' } else { ' ' };
+        let text = format!("[{}] {}", mark, self.text);
+        print_in_rect(panel, self.rect, 0, 0, &text, fg, bg)
+    }
+
+    fn handle_input(&mut self, event: &InputEvent) -> WidgetEvent {
+        if is_activation(event) {
+            self.checked = !self.checked;
+            WidgetEvent::Changed
+        } else {
+            WidgetEvent::Ignored
+        }
+    }
+
+    fn focusable(&self) -> bool {
+        true
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+/// A horizontal bar filled from the left by value, between 0 and 1. Can't be focused.
+pub struct ProgressBar {
+    pub rect: Rect,
+    pub value: f32,
+    pub fill_id: String,
+    pub empty_id: String,
+    pub style: Style,
+}
+
+impl ProgressBar {
+    /// Create a new ProgressBar drawn with full and light shade blocks.
+    pub fn new(rect: Rect, value: f32) -> ProgressBar {
+        ProgressBar {
+            rect,
+            value: value.clamp(0.0, 1.0),
+            fill_id: "█".to_string(),
+            empty_id: "░".to_string(),
+            style: Style::default(),
+        }
+    }
+
+    /// Set the value, clamped between 0 and 1.
+    pub fn set_value(&mut self, value: f32) {
+        self.value = value.clamp(0.0, 1.0);
+    }
+}
+
+impl Widget for ProgressBar {
+    fn rect(&self) -> Rect {
+        self.rect
+    }
+
+    fn set_rect(&mut self, rect: Rect) {
+        self.rect = rect;
+    }
+
+    fn draw(&self, panel: &mut Panel, _focused: bool) -> OozeResult<()> {
+        let (fg, bg) = self.style.colors(false);
+        let filled = (self.rect.size.x as f32 * self.value).round() as u32;
+        let bounds = panel.rect();
+
+        for point in self.rect.points() {
+            if !bounds.contains_point(point) {
+                continue;
+            }
+            let id = if point.x - self.rect.bottom_left.x < filled {
+                &self.fill_id
+            } else {
+                &self.empty_id
+            };
+            panel.place(point.x, point.y, id, fg, bg)?;
+        }
+
+        Ok(())
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
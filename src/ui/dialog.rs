@@ -0,0 +1,320 @@
+use crate::error::OozeResult;
+use crate::geometry::{anchor_rect, Anchor, Dimensions, Point, Rect};
+use crate::input::{InputEvent, Key};
+use crate::terminal::{BorderStyle, Panel, Terminal};
+use crate::ui::{clear_rect, print_in_rect, wrap_text, Style};
+
+/// A bordered popup with a title, some text and a row of choices, see Terminal::open_modal.
+/// With no choices it is a message box that enter or escape closes.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Dialog {
+    pub title: String,
+    pub text: String,
+    pub choices: Vec<String>,
+    pub selected: usize,
+    pub style: Style,
+    pub border: BorderStyle,
+    /// The widest the dialog gets, border included. Longer text is wrapped.
+    pub max_width: u32,
+}
+
+impl Dialog {
+    /// Create a new Dialog with the first choice selected.
+    pub fn new(title: &str, text: &str, choices: Vec<String>) -> Dialog {
+        Dialog {
+            title: title.to_string(),
+            text: text.to_string(),
+            choices,
+            selected: 0,
+            style: Style::default(),
+            border: BorderStyle::Single,
+            max_width: 50,
+        }
+    }
+
+    /// Create a message box with no choices.
+    pub fn message(title: &str, text: &str) -> Dialog {
+        Dialog::new(title, text, Vec::new())
+    }
+
+    /// Return a new Dialog with a changed style.
+    pub fn with_style(self, style: Style) -> Dialog {
+        let mut new = self;
+        new.style = style;
+        new
+    }
+
+    /// Return a new Dialog with a changed border.
+    pub fn with_border(self, border: BorderStyle) -> Dialog {
+        let mut new = self;
+        new.border = border;
+        new
+    }
+
+    /// The width of the choices side by side on one row.
+    fn choices_width(&self) -> u32 {
+        let labels: usize = self.choices.iter().map(|c| c.chars().count() + 2).sum();
+        (labels + 2 * self.choices.len().saturating_sub(1)) as u32
+    }
+
+    /// The size of the text area inside the border and the side padding, plus whether the choices are stacked.
+    /// Fits inside a terminal of term_size where it can.
+    fn content_size(&self, term_size: Point) -> (Point, bool) {
+        let max_width = self.max_width.min(term_size.x).saturating_sub(4).max(1);
+        let longest_line = self
+            .text
+            .split('\n')
+            .map(|line| line.chars().count())
+            .max()
+            .unwrap_or(0) as u32;
+        let title = self.title.chars().count() as u32 + 2;
+        let width = longest_line
+            .max(title)
+            .max(self.choices_width())
+            .min(max_width)
+            .max(1);
+
+        let stacked = self.choices_width() > width;
+        let text_rows = wrap_text(&self.text, width as usize).len() as u32;
+        let height = (text_rows + self.choice_rows(stacked)).min(term_size.y.saturating_sub(2));
+        (Point::new(width, height), stacked)
+    }
+
+    /// The rows the choices take below the text, including the blank row between them.
+    fn choice_rows(&self, stacked: bool) -> u32 {
+        match (self.choices.is_empty(), stacked) {
+            (true, _) => 0,
+            (false, false) => 2,
+            (false, true) => 1 + self.choices.len() as u32,
+        }
+    }
+
+    /// Draw the dialog filling a Panel.
+    fn draw(&self, panel: &mut Panel) -> OozeResult<()> {
+        let (fg, bg) = self.style.colors(false);
+        let rect = panel.rect();
+        clear_rect(panel, rect, bg)?;
+        panel.draw_border(rect, &self.border, fg, bg)?;
+        if !self.title.is_empty() {
+            panel.draw_border_title(rect, &self.title, fg, bg)?;
+        }
+
+        let (size, stacked) = self.content_size(Point::new(rect.size.x, rect.size.y));
+        let content = Rect::new(Point::new(2, 1), size);
+        // the choices keep their rows on short terminals, the text is cut off instead
+        let text_rows = size.y.saturating_sub(self.choice_rows(stacked));
+        let rows = wrap_text(&self.text, size.x as usize);
+        for (row, line) in rows.iter().take(text_rows as usize).enumerate() {
+            print_in_rect(panel, content, row as u32, 0, line, fg, bg)?;
+        }
+
+        let choices_row = (rows.len() as u32).min(text_rows) + 1;
+        let mut column = if stacked {
+            0
+        } else {
+            (size.x - self.choices_width()) / 2
+        };
+        for (i, choice) in self.choices.iter().enumerate() {
+            let (choice_fg, choice_bg) = self.style.colors(i == self.selected);
+            let label = format!("[{}]", choice);
+            if stacked {
+                print_in_rect(
+                    panel,
+                    content,
+                    choices_row + i as u32,
+                    0,
+                    &label,
+                    choice_fg,
+                    choice_bg,
+                )?;
+            } else {
+                print_in_rect(
+                    panel,
+                    content,
+                    choices_row,
+                    column,
+                    &label,
+                    choice_fg,
+                    choice_bg,
+                )?;
+                column += label.chars().count() as u32 + 2;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// What happened to an input event given to Terminal::modal_input.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ModalEvent {
+    /// No dialog is open, the game should handle the event.
+    NotCaptured,
+    /// The top dialog took the event and is still open. Events a dialog has no use for are captured too.
+    Captured,
+    /// The top dialog was closed by picking the choice at this index. Message boxes close with Chosen(0).
+    Chosen(usize),
+    /// The top dialog was closed with escape without picking anything.
+    Dismissed,
+}
+
+/// An open Dialog and the Panel it is drawn on.
+pub struct Modal {
+    pub dialog: Dialog,
+    pub panel: Panel,
+}
+
+impl Modal {
+    /// Lay out a Dialog centered on a terminal with the given Dimensions and draw it on a new Panel.
+    pub fn new(dialog: Dialog, terminal_dims: Dimensions) -> OozeResult<Modal> {
+        let (content, _) = dialog.content_size(terminal_dims.term_size);
+        let size = Point::new(content.x + 4, content.y + 2);
+        let rect = anchor_rect(terminal_dims.rect(), size, Anchor::Center);
+
+        let mut panel = Panel::new(terminal_dims.copy_for_panel(rect.size, rect.bottom_left));
+        dialog.draw(&mut panel)?;
+
+        Ok(Modal { dialog, panel })
+    }
+
+    /// Draw the dialog again after changing it.
+    pub fn redraw(&mut self) -> OozeResult<()> {
+        self.dialog.draw(&mut self.panel)
+    }
+}
+
+impl Terminal {
+    /// Open a Dialog centered on this Terminal, above every Panel and any dialog already open.
+    /// It stays open and takes all input given to Terminal::modal_input until a choice is picked or it is dismissed.
+    pub fn open_modal(&mut self, dialog: Dialog) -> OozeResult<()> {
+        let mut modal = Modal::new(dialog, self.dims)?;
+        modal.panel.set_layer(self.top_layer() + 1);
+        self.modals.push(modal);
+
+        Ok(())
+    }
+
+    /// Check if a dialog is open.
+    pub fn has_modal(&self) -> bool {
+        !self.modals.is_empty()
+    }
+
+    /// Close the top dialog without picking anything and return it.
+    pub fn close_modal(&mut self) -> Option<Dialog> {
+        self.modals.pop().map(|modal| modal.dialog)
+    }
+
+    /// Give an input event to the top dialog. While a dialog is open, every event goes to it, mouse and close events included:
+    /// the arrow keys, tab and shift-tab move between choices, enter picks one and escape dismisses the dialog.
+    /// Check for NotCaptured to know when the game should handle the event itself.
+    pub fn modal_input(&mut self, event: &InputEvent) -> OozeResult<ModalEvent> {
+        if self.modals.is_empty() {
+            return Ok(ModalEvent::NotCaptured);
+        }
+        let key = match event {
+            InputEvent::KeyPressed { key, .. } => *key,
+            _ => return Ok(ModalEvent::Captured),
+        };
+
+        let result = {
+            let modal = self.modals.last_mut().unwrap();
+            let count = modal.dialog.choices.len();
+            let selected = modal.dialog.selected;
+            match key {
+                Key::Enter => ModalEvent::Chosen(selected),
+                Key::Escape => ModalEvent::Dismissed,
+                Key::Left | Key::Up | Key::BackTab if count > 0 => {
+                    modal.dialog.selected = (selected + count - 1) % count;
+                    modal.redraw()?;
+                    ModalEvent::Captured
+                }
+                Key::Right | Key::Down | Key::Tab if count > 0 => {
+                    modal.dialog.selected = (selected + 1) % count;
+                    modal.redraw()?;
+                    ModalEvent::Captured
+                }
+                _ => ModalEvent::Captured,
+            }
+        };
+
+        match result {
+            ModalEvent::Chosen(_) | ModalEvent::Dismissed => {
+                self.modals.pop();
+            }
+            _ => {}
+        }
+
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn press(key: Key) -> InputEvent {
+        InputEvent::KeyPressed {
+            key,
+            ctrl: false,
+            alt: false,
+        }
+    }
+
+    fn terminal_with_dialog() -> Terminal {
+        let mut terminal = Terminal::new(Dimensions::new(1, 1, 30, 10, 0, 0));
+        let choices = vec!["Yes".to_string(), "No".to_string()];
+        terminal
+            .open_modal(Dialog::new("Quit", "Really quit?", choices))
+            .unwrap();
+        terminal
+    }
+
+    #[test]
+    fn events_reach_the_game_without_a_dialog() {
+        let mut terminal = Terminal::new(Dimensions::new(1, 1, 30, 10, 0, 0));
+
+        assert_eq!(
+            terminal.modal_input(&press(Key::Enter)).unwrap(),
+            ModalEvent::NotCaptured
+        );
+        assert_eq!(
+            terminal.modal_input(&InputEvent::MouseWheel(1)).unwrap(),
+            ModalEvent::NotCaptured
+        );
+    }
+
+    #[test]
+    fn every_event_is_captured_while_a_dialog_is_open() {
+        let mut terminal = terminal_with_dialog();
+        let events = [
+            press(Key::Char('x')),
+            InputEvent::MouseMoved(Point::new(1, 1)),
+            InputEvent::MouseWheel(-2),
+            InputEvent::Resized(30, 10),
+            InputEvent::Close,
+        ];
+
+        for event in &events {
+            assert_eq!(terminal.modal_input(event).unwrap(), ModalEvent::Captured);
+        }
+        assert!(terminal.has_modal());
+    }
+
+    #[test]
+    fn choices_are_picked_or_dismissed() {
+        let mut terminal = terminal_with_dialog();
+        terminal.modal_input(&press(Key::Right)).unwrap();
+        assert_eq!(
+            terminal.modal_input(&press(Key::Enter)).unwrap(),
+            ModalEvent::Chosen(1)
+        );
+        assert!(!terminal.has_modal());
+
+        let mut terminal = terminal_with_dialog();
+        assert_eq!(
+            terminal.modal_input(&press(Key::Escape)).unwrap(),
+            ModalEvent::Dismissed
+        );
+        assert!(!terminal.has_modal());
+    }
+}
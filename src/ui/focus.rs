@@ -0,0 +1,120 @@
+use crate::error::OozeResult;
+use crate::input::{InputEvent, Key};
+use crate::terminal::Panel;
+use crate::ui::{Widget, WidgetEvent};
+
+/// A group of widgets drawn into one Panel, with keyboard focus moving between them with tab and shift-tab.
+/// Input goes to the focused widget.
+pub struct Ui {
+    pub widgets: Vec<Box<dyn Widget>>,
+
+    focus: Option<usize>,
+}
+
+impl Ui {
+    /// Create an empty Ui.
+    pub fn new() -> Ui {
+        Ui {
+            widgets: Vec::new(),
+            focus: None,
+        }
+    }
+
+    /// Add a widget and return its index. The first focusable widget added gets the focus.
+    pub fn add<W: Widget + 'static>(&mut self, widget: W) -> usize {
+        let index = self.widgets.len();
+        if self.focus.is_none() && widget.focusable() {
+            self.focus = Some(index);
+        }
+        self.widgets.push(Box::new(widget));
+        index
+    }
+
+    /// The widget at index, if it is a W.
+    pub fn get<W: Widget + 'static>(&self, index: usize) -> Option<&W> {
+        self.widgets
+            .get(index)
+            .and_then(|widget| widget.as_any().downcast_ref())
+    }
+
+    /// The widget at index mutably, if it is a W.
+    pub fn get_mut<W: Widget + 'static>(&mut self, index: usize) -> Option<&mut W> {
+        self.widgets
+            .get_mut(index)
+            .and_then(|widget| widget.as_any_mut().downcast_mut())
+    }
+
+    /// The index of the focused widget.
+    pub fn focused(&self) -> Option<usize> {
+        self.focus
+    }
+
+    /// Focus the widget at index, if it can take focus.
+    pub fn set_focus(&mut self, index: usize) {
+        if self.widgets.get(index).map_or(false, |w| w.focusable()) {
+            self.focus = Some(index);
+        }
+    }
+
+    /// Move the focus to the next focusable widget, wrapping around.
+    pub fn focus_next(&mut self) {
+        self.move_focus(true);
+    }
+
+    /// Move the focus to the previous focusable widget, wrapping around.
+    pub fn focus_previous(&mut self) {
+        self.move_focus(false);
+    }
+
+    fn move_focus(&mut self, forward: bool) {
+        let count = self.widgets.len();
+        if count == 0 {
+            return;
+        }
+        let current = self.focus.unwrap_or(if forward { count - 1 } else { 0 });
+        for step in 1..=count {
+            let index = if forward {
+                (current + step) % count
+            } else {
+                (current + count - step % count) % count
+            };
+            if self.widgets[index].focusable() {
+                self.focus = Some(index);
+                return;
+            }
+        }
+    }
+
+    /// Give an input event to the focused widget. Tab and shift-tab move the focus if the widget doesn't use them.
+    /// Returns the index of the widget and what it did, or None if no widget used the event.
+    pub fn handle_input(&mut self, event: &InputEvent) -> Option<(usize, WidgetEvent)> {
+        if let Some(index) = self.focus {
+            let result = self.widgets[index].handle_input(event);
+            if result != WidgetEvent::Ignored {
+                return Some((index, result));
+            }
+        }
+
+        match event.key() {
+            Some(Key::Tab) => self.focus_next(),
+            Some(Key::BackTab) => self.focus_previous(),
+            _ => return None,
+        }
+        self.focus.map(|index| (index, WidgetEvent::Consumed))
+    }
+
+    /// Draw every widget into the Panel.
+    pub fn draw(&self, panel: &mut Panel) -> OozeResult<()> {
+        for (index, widget) in self.widgets.iter().enumerate() {
+            widget.draw(panel, self.focus == Some(index))?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for Ui {
+    fn default() -> Ui {
+        Ui::new()
+    }
+}
@@ -0,0 +1,222 @@
+use std::any::Any;
+
+use crate::error::OozeResult;
+use crate::geometry::Rect;
+use crate::input::{InputEvent, Key};
+use crate::terminal::Panel;
+use crate::ui::{clear_rect, print_in_rect, Style, Widget, WidgetEvent};
+
+/// A scrolling list of items with one selected, also usable as a menu.
+/// The selection moves with the arrow keys, home, end, page up and page down, and enter chooses it.
+pub struct List {
+    pub rect: Rect,
+    pub items: Vec<String>,
+    pub selected: usize,
+    pub style: Style,
+
+    scroll: usize,
+}
+
+impl List {
+    /// Create a new List with the first item selected.
+    pub fn new(rect: Rect, items: Vec<String>) -> List {
+        List {
+            rect,
+            items,
+            selected: 0,
+            style: Style::default(),
+            scroll: 0,
+        }
+    }
+
+    /// The selected item, if there are any.
+    pub fn selected_item(&self) -> Option<&str> {
+        self.items.get(self.selected).map(|item| item.as_str())
+    }
+
+    /// Select the item at index, clamped to the list, and scroll it into view.
+    pub fn select(&mut self, index: usize) {
+        self.selected = index.min(self.items.len().saturating_sub(1));
+        self.scroll = self.visible_start(self.scroll);
+    }
+
+    /// The first visible item when the view starts at scroll, moved just enough to show the selected item.
+    fn visible_start(&self, scroll: usize) -> usize {
+        let height = self.rect.size.y.max(1) as usize;
+        if self.selected < scroll {
+            self.selected
+        } else if self.selected >= scroll + height {
+            self.selected + 1 - height
+        } else {
+            scroll
+        }
+    }
+}
+
+impl Widget for List {
+    fn rect(&self) -> Rect {
+        self.rect
+    }
+
+    fn set_rect(&mut self, rect: Rect) {
+        self.rect = rect;
+        self.scroll = self.visible_start(self.scroll);
+    }
+
+    fn draw(&self, panel: &mut Panel, focused: bool) -> OozeResult<()> {
+        let (fg, bg) = self.style.colors(false);
+        clear_rect(panel, self.rect, bg)?;
+
+        let start = self.visible_start(self.scroll);
+        let height = self.rect.size.y as usize;
+        for (row, (index, item)) in self
+            .items
+            .iter()
+            .enumerate()
+            .skip(start)
+            .take(height)
+            .enumerate()
+        {
+            let (marker, (item_fg, item_bg)) = if index == self.selected {
+                ("> ", self.style.colors(focused))
+            } else {
+                ("  ", (fg, bg))
+            };
+            let line = format!("{}{}", marker, item);
+            let padded = format!("{:width$}", line, width = self.rect.size.x as usize);
+            print_in_rect(panel, self.rect, row as u32, 0, &padded, item_fg, item_bg)?;
+        }
+
+        Ok(())
+    }
+
+    fn handle_input(&mut self, event: &InputEvent) -> WidgetEvent {
+        if self.items.is_empty() {
+            return WidgetEvent::Ignored;
+        }
+        let page = self.rect.size.y.max(1) as usize;
+        let last = self.items.len() - 1;
+
+        let target = match event.key() {
+            Some(Key::Enter) => return WidgetEvent::Activated,
+            Some(Key::Up) => self.selected.saturating_sub(1),
+            Some(Key::Down) => (self.selected + 1).min(last),
+            Some(Key::PageUp) => self.selected.saturating_sub(page),
+            Some(Key::PageDown) => (self.selected + page).min(last),
+            Some(Key::Home) => 0,
+            Some(Key::End) => last,
+            _ => return WidgetEvent::Ignored,
+        };
+
+        if target == self.selected {
+            WidgetEvent::Consumed
+        } else {
+            self.select(target);
+            WidgetEvent::Changed
+        }
+    }
+
+    fn focusable(&self) -> bool {
+        true
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::{Dimensions, Point};
+
+    fn press(key: Key) -> InputEvent {
+        InputEvent::KeyPressed {
+            key,
+            ctrl: false,
+            alt: false,
+        }
+    }
+
+    /// A List of the items a to e showing three at a time.
+    fn list() -> List {
+        let items = ["a", "b", "c", "d", "e"].iter().map(|s| s.to_string());
+        List::new(
+            Rect::new(Point::new(0, 0), Point::new(4, 3)),
+            items.collect(),
+        )
+    }
+
+    fn shown(list: &List) -> String {
+        let mut panel = Panel::new(Dimensions::new(1, 1, 4, 3, 0, 0));
+        list.draw(&mut panel, true).unwrap();
+        panel.to_plain_text()
+    }
+
+    #[test]
+    fn selection_moves_and_stops_at_the_ends() {
+        let mut list = list();
+        assert_eq!(list.handle_input(&press(Key::Up)), WidgetEvent::Consumed);
+        assert_eq!(list.handle_input(&press(Key::Down)), WidgetEvent::Changed);
+        assert_eq!(list.selected_item(), Some("b"));
+
+        list.handle_input(&press(Key::End));
+        assert_eq!(list.selected, 4);
+        assert_eq!(list.handle_input(&press(Key::Down)), WidgetEvent::Consumed);
+        list.handle_input(&press(Key::PageUp));
+        assert_eq!(list.selected, 1);
+        list.handle_input(&press(Key::PageDown));
+        assert_eq!(list.selected, 4);
+        list.handle_input(&press(Key::Home));
+        assert_eq!(list.selected, 0);
+
+        assert_eq!(
+            list.handle_input(&press(Key::Enter)),
+            WidgetEvent::Activated
+        );
+        assert_eq!(
+            list.handle_input(&press(Key::Char('x'))),
+            WidgetEvent::Ignored
+        );
+
+        list.select(10);
+        assert_eq!(list.selected_item(), Some("e"));
+    }
+
+    #[test]
+    fn empty_lists_ignore_input() {
+        let mut list = List::new(Rect::new(Point::new(0, 0), Point::new(4, 3)), Vec::new());
+        assert_eq!(list.handle_input(&press(Key::Down)), WidgetEvent::Ignored);
+        assert_eq!(list.handle_input(&press(Key::Enter)), WidgetEvent::Ignored);
+        assert_eq!(list.selected_item(), None);
+    }
+
+    #[test]
+    fn scrolls_to_keep_the_selection_in_view() {
+        let mut list = list();
+        assert_eq!(shown(&list), "> a \n  b \n  c ");
+
+        list.handle_input(&press(Key::End));
+        assert_eq!(shown(&list), "  c \n  d \n> e ");
+
+        // moving up inside the view doesn't scroll
+        list.handle_input(&press(Key::Up));
+        list.handle_input(&press(Key::Up));
+        assert_eq!(shown(&list), "> c \n  d \n  e ");
+        list.handle_input(&press(Key::Up));
+        assert_eq!(shown(&list), "> b \n  c \n  d ");
+    }
+
+    #[test]
+    fn shrinking_keeps_the_selection_in_view() {
+        let mut list = list();
+        list.select(4);
+        list.set_rect(Rect::new(Point::new(0, 2), Point::new(4, 1)));
+
+        assert_eq!(shown(&list), "> e \n    \n    ");
+    }
+}
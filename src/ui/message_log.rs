@@ -0,0 +1,229 @@
+use std::any::Any;
+use std::collections::VecDeque;
+
+use crate::error::OozeResult;
+use crate::geometry::Rect;
+use crate::input::InputEvent;
+use crate::terminal::Panel;
+use crate::ui::{wrap_rows, Scrollback, Style, Widget, WidgetEvent};
+
+/// One entry of a MessageLog, shown as "text xcount" once it has been repeated.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Message {
+    pub text: String,
+    pub fg_color: [f32; 4],
+    pub count: u32,
+}
+
+impl Message {
+    /// Create a new Message that has been said once.
+    pub fn new(text: &str, fg_color: [f32; 4]) -> Message {
+        Message {
+            text: text.to_string(),
+            fg_color,
+            count: 1,
+        }
+    }
+
+    /// The text as it is shown, with the repeat count if there is one.
+    pub fn display_text(&self) -> String {
+        if self.count > 1 {
+            format!("{} x{}", self.text, self.count)
+        } else {
+            self.text.clone()
+        }
+    }
+}
+
+/// The game's message log: colored messages wrapped to fit, newest at the bottom.
+/// Keeps at most max_messages messages, merges a message repeated right after itself into one with a count,
+/// and fades older messages towards the background color.
+/// Can be scrolled back with the arrow keys, page up, page down, home, end and the mouse wheel.
+pub struct MessageLog {
+    pub rect: Rect,
+    pub messages: VecDeque<Message>,
+    pub max_messages: usize,
+    pub style: Style,
+
+    /// Whether a message equal to the newest one bumps its count instead of being added again.
+    pub merge_repeats: bool,
+    /// How much each message is faded compared to the one after it, from 0.0 for no fading.
+    pub fade_step: f32,
+    /// How far messages can fade, from 0.0 for invisible to 1.0 for not fading at all.
+    pub min_brightness: f32,
+
+    scrollback: Scrollback,
+}
+
+impl MessageLog {
+    /// Create a new empty MessageLog that merges repeats and fades older messages.
+    pub fn new(rect: Rect, max_messages: usize) -> MessageLog {
+        MessageLog {
+            rect,
+            messages: VecDeque::new(),
+            max_messages,
+            style: Style::default(),
+            merge_repeats: true,
+            fade_step: 0.15,
+            min_brightness: 0.4,
+            scrollback: Scrollback::new(),
+        }
+    }
+
+    /// Add a message in the style's foreground color.
+    pub fn push(&mut self, text: &str) {
+        let color = self.style.fg_color;
+        self.push_colored(text, color);
+    }
+
+    /// Add a message in the given color, dropping the oldest message if the log is full.
+    /// A scrolled back view stays where it is.
+    pub fn push_colored(&mut self, text: &str, fg_color: [f32; 4]) {
+        let rows_before = self.rows().len();
+
+        let repeated = self.merge_repeats
+            && self
+                .messages
+                .back()
+                .map_or(false, |last| last.text == text && last.fg_color == fg_color);
+        if repeated {
+            if let Some(last) = self.messages.back_mut() {
+                last.count += 1;
+            }
+        } else {
+            self.messages.push_back(Message::new(text, fg_color));
+            while self.messages.len() > self.max_messages {
+                self.messages.pop_front();
+            }
+        }
+
+        let rows = self.rows().len();
+        self.scrollback.keep_view(
+            rows.saturating_sub(rows_before),
+            rows,
+            self.rect.size.y as usize,
+        );
+    }
+
+    /// Remove every message.
+    pub fn clear(&mut self) {
+        self.messages.clear();
+        self.scrollback.reset();
+    }
+
+    /// How many wrapped rows the view is scrolled up from the newest message.
+    pub fn scroll(&self) -> usize {
+        self.scrollback.scroll()
+    }
+
+    /// Scroll the view up by rows, or down for negative rows, keeping it inside the log.
+    pub fn scroll_by(&mut self, rows: i32) {
+        let row_count = self.rows().len();
+        self.scrollback
+            .scroll_by(rows, row_count, self.rect.size.y as usize);
+    }
+
+    /// The brightness of a message, age messages before the newest one.
+    pub fn brightness(&self, age: usize) -> f32 {
+        let faded = 1.0 - self.fade_step * age as f32;
+        // min() also turns a NaN min_brightness into 1.0, as clamp panics on NaN or a minimum above the maximum
+        faded.clamp(self.min_brightness.min(1.0), 1.0)
+    }
+
+    /// The messages wrapped to the width of the region, oldest first, faded towards the background color by their age.
+    fn rows(&self) -> Vec<(String, [f32; 4])> {
+        let (_, bg) = self.style.colors(false);
+        let newest = self.messages.len().saturating_sub(1);
+        let texts: Vec<String> = self.messages.iter().map(Message::display_text).collect();
+        let lines = self
+            .messages
+            .iter()
+            .zip(&texts)
+            .enumerate()
+            .map(|(i, (message, text))| {
+                let brightness = self.brightness(newest - i);
+                let mut faded = message.fg_color;
+                for channel in 0..3 {
+                    faded[channel] =
+                        bg[channel] + (message.fg_color[channel] - bg[channel]) * brightness;
+                }
+                (text.as_str(), faded)
+            });
+        wrap_rows(lines, self.rect.size.x as usize)
+    }
+}
+
+impl Widget for MessageLog {
+    fn rect(&self) -> Rect {
+        self.rect
+    }
+
+    fn set_rect(&mut self, rect: Rect) {
+        self.rect = rect;
+        // rewrapping changes the number of rows, so keep the view inside the log
+        self.scroll_by(0);
+    }
+
+    fn draw(&self, panel: &mut Panel, _focused: bool) -> OozeResult<()> {
+        let (_, bg) = self.style.colors(false);
+        self.scrollback.draw(panel, self.rect, &self.rows(), bg)
+    }
+
+    fn handle_input(&mut self, event: &InputEvent) -> WidgetEvent {
+        let row_count = self.rows().len();
+        self.scrollback
+            .handle_input(event, row_count, self.rect.size.y as usize)
+    }
+
+    fn focusable(&self) -> bool {
+        true
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::{Dimensions, Point};
+
+    #[test]
+    fn fading() {
+        let mut log = MessageLog::new(Rect::of_size(Point::new(10, 3)), 10);
+        assert_eq!(log.brightness(0), 1.0);
+        assert!((log.brightness(2) - 0.7).abs() < 1e-6);
+        assert_eq!(log.brightness(100), 0.4);
+
+        log.min_brightness = 2.0;
+        assert_eq!(log.brightness(3), 1.0);
+        log.min_brightness = f32::NAN;
+        assert_eq!(log.brightness(3), 1.0);
+        log.min_brightness = 0.4;
+        log.fade_step = -1.0;
+        assert_eq!(log.brightness(3), 1.0);
+    }
+
+    #[test]
+    fn older_messages_are_drawn_faded() {
+        let mut panel = Panel::new(Dimensions::new(8, 8, 10, 3, 0, 0));
+        let mut log = MessageLog::new(panel.rect(), 10);
+        log.fade_step = 0.5;
+        log.push_colored("old", [1.0, 1.0, 1.0, 1.0]);
+        log.push_colored("new", [1.0, 1.0, 1.0, 1.0]);
+        log.draw(&mut panel, false).unwrap();
+
+        // the log is drawn from the top row down, over a black background
+        let old = panel.get(Point::new(0, 2)).unwrap();
+        let new = panel.get(Point::new(0, 1)).unwrap();
+        assert_eq!(old.sprite_id, "o");
+        assert_eq!(old.fg_color, [0.5, 0.5, 0.5, 1.0]);
+        assert_eq!(new.sprite_id, "n");
+        assert_eq!(new.fg_color, [1.0, 1.0, 1.0, 1.0]);
+    }
+}
@@ -0,0 +1,21 @@
+mod basic;
+mod dialog;
+mod focus;
+mod list;
+mod message_log;
+mod scrollback;
+mod text_input;
+mod text_log;
+mod tooltip;
+mod widget;
+
+pub use basic::*;
+pub use dialog::*;
+pub use focus::*;
+pub use list::*;
+pub use message_log::*;
+pub use scrollback::*;
+pub use text_input::*;
+pub use text_log::*;
+pub use tooltip::*;
+pub use widget::*;
@@ -0,0 +1,105 @@
+use crate::error::OozeResult;
+use crate::geometry::Rect;
+use crate::input::{InputEvent, Key};
+use crate::terminal::Panel;
+use crate::ui::{clear_rect, print_in_rect, wrap_text, WidgetEvent};
+
+/// Split colored lines into rows no wider than width, oldest first, see wrap_text.
+pub fn wrap_rows<'a, I>(lines: I, width: usize) -> Vec<(String, [f32; 4])>
+where
+    I: IntoIterator<Item = (&'a str, [f32; 4])>,
+{
+    lines
+        .into_iter()
+        .flat_map(|(text, color)| {
+            wrap_text(text, width)
+                .into_iter()
+                .map(move |row| (row, color))
+        })
+        .collect()
+}
+
+/// How far a view of colored rows with the newest at the bottom is scrolled back, for log widgets like TextLog and MessageLog.
+/// Scrolls with the arrow keys, page up, page down, home, end and the mouse wheel.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Scrollback {
+    scroll: usize,
+}
+
+impl Scrollback {
+    /// Create a new Scrollback showing the newest rows.
+    pub fn new() -> Scrollback {
+        Scrollback { scroll: 0 }
+    }
+
+    /// How many rows the view is scrolled up from the newest row.
+    pub fn scroll(&self) -> usize {
+        self.scroll
+    }
+
+    /// Go back to showing the newest rows.
+    pub fn reset(&mut self) {
+        self.scroll = 0;
+    }
+
+    /// Scroll the view up by rows, or down for negative rows, keeping it inside row_count rows shown height at a time.
+    pub fn scroll_by(&mut self, rows: i32, row_count: usize, height: usize) {
+        let max_scroll = row_count.saturating_sub(height);
+        let scroll = if rows < 0 {
+            self.scroll.saturating_sub(rows.unsigned_abs() as usize)
+        } else {
+            self.scroll.saturating_add(rows as usize)
+        };
+        self.scroll = scroll.min(max_scroll);
+    }
+
+    /// Keep a scrolled back view on the same rows after added rows came in at the bottom.
+    pub fn keep_view(&mut self, added: usize, row_count: usize, height: usize) {
+        if self.scroll > 0 {
+            self.scroll_by(added as i32, row_count, height);
+        }
+    }
+
+    /// Scroll for a key or mouse wheel event.
+    pub fn handle_input(
+        &mut self,
+        event: &InputEvent,
+        row_count: usize,
+        height: usize,
+    ) -> WidgetEvent {
+        let page = height.max(1) as i32;
+        match event {
+            InputEvent::MouseWheel(notches) => self.scroll_by(*notches, row_count, height),
+            _ => match event.key() {
+                Some(Key::Up) => self.scroll_by(1, row_count, height),
+                Some(Key::Down) => self.scroll_by(-1, row_count, height),
+                Some(Key::PageUp) => self.scroll_by(page, row_count, height),
+                Some(Key::PageDown) => self.scroll_by(-page, row_count, height),
+                Some(Key::Home) => self.scroll_by(i32::MAX, row_count, height),
+                Some(Key::End) => self.reset(),
+                _ => return WidgetEvent::Ignored,
+            },
+        }
+
+        WidgetEvent::Consumed
+    }
+
+    /// Clear a region of a Panel and draw the rows it shows at this scroll, oldest at the top.
+    pub fn draw(
+        &self,
+        panel: &mut Panel,
+        rect: Rect,
+        rows: &[(String, [f32; 4])],
+        bg_color: [f32; 4],
+    ) -> OozeResult<()> {
+        clear_rect(panel, rect, bg_color)?;
+
+        let end = rows.len().saturating_sub(self.scroll);
+        let start = end.saturating_sub(rect.size.y as usize);
+        for (row, (text, color)) in rows[start..end].iter().enumerate() {
+            print_in_rect(panel, rect, row as u32, 0, text, *color, bg_color)?;
+        }
+
+        Ok(())
+    }
+}
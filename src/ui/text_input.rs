@@ -0,0 +1,276 @@
+use std::any::Any;
+
+use crate::error::OozeResult;
+use crate::geometry::Rect;
+use crate::input::{InputEvent, Key};
+use crate::terminal::Panel;
+use crate::ui::{clear_rect, print_in_rect, Style, Widget, WidgetEvent};
+
+/// A single line of editable text with a cursor. Enter activates it.
+/// Text longer than the region scrolls sideways to keep the cursor in view.
+pub struct TextInput {
+    pub rect: Rect,
+    pub text: String,
+    pub max_length: Option<usize>,
+    pub style: Style,
+
+    cursor: usize,
+}
+
+impl TextInput {
+    /// Create a new empty TextInput, optionally limited to max_length characters.
+    pub fn new(rect: Rect, max_length: Option<usize>) -> TextInput {
+        TextInput {
+            rect,
+            text: String::new(),
+            max_length,
+            style: Style::default(),
+            cursor: 0,
+        }
+    }
+
+    /// The cursor position, in characters from the start of the text.
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    /// Replace the text and move the cursor to its end.
+    pub fn set_text(&mut self, text: &str) {
+        self.text = text.to_string();
+        self.cursor = self.text.chars().count();
+    }
+
+    /// The byte index of a character index in the text.
+    fn byte_index(&self, index: usize) -> usize {
+        self.text
+            .char_indices()
+            .nth(index)
+            .map_or(self.text.len(), |(i, _)| i)
+    }
+}
+
+impl Widget for TextInput {
+    fn rect(&self) -> Rect {
+        self.rect
+    }
+
+    fn set_rect(&mut self, rect: Rect) {
+        self.rect = rect;
+    }
+
+    fn draw(&self, panel: &mut Panel, focused: bool) -> OozeResult<()> {
+        let (fg, bg) = self.style.colors(false);
+        clear_rect(panel, self.rect, bg)?;
+
+        let width = self.rect.size.x as usize;
+        if width == 0 {
+            return Ok(());
+        }
+        let start = (self.cursor + 1).saturating_sub(width);
+        let shown: String = self.text.chars().skip(start).take(width).collect();
+        print_in_rect(panel, self.rect, 0, 0, &shown, fg, bg)?;
+
+        if focused {
+            let (cursor_fg, cursor_bg) = self.style.colors(true);
+            let under_cursor = self.text.chars().nth(self.cursor).unwrap_or(' ');
+            let column = (self.cursor - start) as u32;
+            print_in_rect(
+                panel,
+                self.rect,
+                0,
+                column,
+                &under_cursor.to_string(),
+                cursor_fg,
+                cursor_bg,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn handle_input(&mut self, event: &InputEvent) -> WidgetEvent {
+        let length = self.text.chars().count();
+        match event {
+            InputEvent::KeyPressed {
+                key: Key::Char(c),
+                ctrl: false,
+                ..
+            } => {
+                if self.max_length.map_or(false, |max| length >= max) {
+                    return WidgetEvent::Consumed;
+                }
+                let at = self.byte_index(self.cursor);
+                self.text.insert(at, *c);
+                self.cursor += 1;
+                WidgetEvent::Changed
+            }
+            InputEvent::KeyPressed { key, .. } => match key {
+                Key::Enter => WidgetEvent::Activated,
+                Key::Backspace if self.cursor > 0 => {
+                    self.cursor -= 1;
+                    let at = self.byte_index(self.cursor);
+                    self.text.remove(at);
+                    WidgetEvent::Changed
+                }
+                Key::Delete if self.cursor < length => {
+                    let at = self.byte_index(self.cursor);
+                    self.text.remove(at);
+                    WidgetEvent::Changed
+                }
+                Key::Backspace | Key::Delete => WidgetEvent::Consumed,
+                Key::Left => {
+                    self.cursor = self.cursor.saturating_sub(1);
+                    WidgetEvent::Consumed
+                }
+                Key::Right => {
+                    self.cursor = (self.cursor + 1).min(length);
+                    WidgetEvent::Consumed
+                }
+                Key::Home => {
+                    self.cursor = 0;
+                    WidgetEvent::Consumed
+                }
+                Key::End => {
+                    self.cursor = length;
+                    WidgetEvent::Consumed
+                }
+                _ => WidgetEvent::Ignored,
+            },
+            _ => WidgetEvent::Ignored,
+        }
+    }
+
+    fn focusable(&self) -> bool {
+        true
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::{Dimensions, Point};
+
+    fn press(key: Key) -> InputEvent {
+        InputEvent::KeyPressed {
+            key,
+            ctrl: false,
+            alt: false,
+        }
+    }
+
+    fn input(max_length: Option<usize>) -> TextInput {
+        TextInput::new(Rect::new(Point::new(0, 0), Point::new(4, 1)), max_length)
+    }
+
+    fn type_text(input: &mut TextInput, text: &str) {
+        for c in text.chars() {
+            input.handle_input(&press(Key::Char(c)));
+        }
+    }
+
+    #[test]
+    fn typing_inserts_at_the_cursor() {
+        let mut input = input(None);
+        type_text(&mut input, "ac");
+        assert_eq!(input.handle_input(&press(Key::Left)), WidgetEvent::Consumed);
+        assert_eq!(
+            input.handle_input(&press(Key::Char('b'))),
+            WidgetEvent::Changed
+        );
+
+        assert_eq!(input.text, "abc");
+        assert_eq!(input.cursor(), 2);
+
+        let ctrl_c = InputEvent::KeyPressed {
+            key: Key::Char('c'),
+            ctrl: true,
+            alt: false,
+        };
+        assert_eq!(input.handle_input(&ctrl_c), WidgetEvent::Ignored);
+        assert_eq!(input.text, "abc");
+    }
+
+    #[test]
+    fn backspace_and_delete() {
+        let mut input = input(None);
+        input.set_text("héllo");
+
+        assert_eq!(
+            input.handle_input(&press(Key::Delete)),
+            WidgetEvent::Consumed
+        );
+        assert_eq!(
+            input.handle_input(&press(Key::Backspace)),
+            WidgetEvent::Changed
+        );
+        assert_eq!(input.text, "héll");
+
+        input.handle_input(&press(Key::Home));
+        assert_eq!(
+            input.handle_input(&press(Key::Backspace)),
+            WidgetEvent::Consumed
+        );
+        input.handle_input(&press(Key::Right));
+        input.handle_input(&press(Key::Delete));
+        assert_eq!(input.text, "hll");
+        assert_eq!(input.cursor(), 1);
+    }
+
+    #[test]
+    fn cursor_stays_in_the_text() {
+        let mut input = input(None);
+        input.set_text("abc");
+        assert_eq!(input.cursor(), 3);
+
+        input.handle_input(&press(Key::Right));
+        assert_eq!(input.cursor(), 3);
+        input.handle_input(&press(Key::Home));
+        input.handle_input(&press(Key::Left));
+        assert_eq!(input.cursor(), 0);
+        input.handle_input(&press(Key::End));
+        assert_eq!(input.cursor(), 3);
+
+        assert_eq!(
+            input.handle_input(&press(Key::Enter)),
+            WidgetEvent::Activated
+        );
+        assert_eq!(input.handle_input(&press(Key::Up)), WidgetEvent::Ignored);
+    }
+
+    #[test]
+    fn max_length() {
+        let mut input = input(Some(3));
+        type_text(&mut input, "abc");
+        assert_eq!(
+            input.handle_input(&press(Key::Char('d'))),
+            WidgetEvent::Consumed
+        );
+        assert_eq!(input.text, "abc");
+
+        input.handle_input(&press(Key::Backspace));
+        type_text(&mut input, "de");
+        assert_eq!(input.text, "abd");
+    }
+
+    #[test]
+    fn long_text_scrolls_to_the_cursor() {
+        let mut input = input(None);
+        input.set_text("abcdefg");
+
+        let mut panel = Panel::new(Dimensions::new(1, 1, 4, 1, 0, 0));
+        input.draw(&mut panel, true).unwrap();
+        assert_eq!(panel.to_plain_text(), "efg ");
+
+        input.handle_input(&press(Key::Home));
+        input.draw(&mut panel, true).unwrap();
+        assert_eq!(panel.to_plain_text(), "abcd");
+    }
+}
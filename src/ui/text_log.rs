@@ -0,0 +1,99 @@
+use std::any::Any;
+use std::collections::VecDeque;
+
+use crate::error::OozeResult;
+use crate::geometry::Rect;
+use crate::input::InputEvent;
+use crate::terminal::Panel;
+use crate::ui::{wrap_rows, Scrollback, Style, Widget, WidgetEvent};
+
+/// Lines of colored text that are wrapped to fit, newest at the bottom.
+/// Keeps at most max_lines lines and can be scrolled back with the arrow keys, page up, page down, home, end and the mouse wheel.
+pub struct TextLog {
+    pub rect: Rect,
+    pub lines: VecDeque<(String, [f32; 4])>,
+    pub max_lines: usize,
+    pub style: Style,
+
+    scrollback: Scrollback,
+}
+
+impl TextLog {
+    /// Create a new empty TextLog.
+    pub fn new(rect: Rect, max_lines: usize) -> TextLog {
+        TextLog {
+            rect,
+            lines: VecDeque::new(),
+            max_lines,
+            style: Style::default(),
+            scrollback: Scrollback::new(),
+        }
+    }
+
+    /// Add a line in the style's foreground color.
+    pub fn push(&mut self, text: &str) {
+        let color = self.style.fg_color;
+        self.push_colored(text, color);
+    }
+
+    /// Add a line in the given color, dropping the oldest line if the log is full.
+    pub fn push_colored(&mut self, text: &str, fg_color: [f32; 4]) {
+        self.lines.push_back((text.to_string(), fg_color));
+        while self.lines.len() > self.max_lines {
+            self.lines.pop_front();
+        }
+    }
+
+    /// Remove every line.
+    pub fn clear(&mut self) {
+        self.lines.clear();
+        self.scrollback.reset();
+    }
+
+    /// How many wrapped rows the view is scrolled up from the newest line.
+    pub fn scroll(&self) -> usize {
+        self.scrollback.scroll()
+    }
+
+    /// The lines wrapped to the width of the region, oldest first.
+    fn rows(&self) -> Vec<(String, [f32; 4])> {
+        let lines = self
+            .lines
+            .iter()
+            .map(|(text, color)| (text.as_str(), *color));
+        wrap_rows(lines, self.rect.size.x as usize)
+    }
+}
+
+impl Widget for TextLog {
+    fn rect(&self) -> Rect {
+        self.rect
+    }
+
+    fn set_rect(&mut self, rect: Rect) {
+        self.rect = rect;
+    }
+
+    fn draw(&self, panel: &mut Panel, _focused: bool) -> OozeResult<()> {
+        let (_, bg) = self.style.colors(false);
+        self.scrollback.draw(panel, self.rect, &self.rows(), bg)
+    }
+
+    fn handle_input(&mut self, event: &InputEvent) -> WidgetEvent {
+        let row_count = self.rows().len();
+        self.scrollback
+            .handle_input(event, row_count, self.rect.size.y as usize)
+    }
+
+    fn focusable(&self) -> bool {
+        true
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
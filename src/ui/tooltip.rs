@@ -0,0 +1,110 @@
+use crate::error::OozeResult;
+use crate::geometry::{Dimensions, Point, Rect};
+use crate::terminal::{BorderStyle, Panel, Terminal};
+use crate::ui::{clear_rect, print_in_rect, wrap_text, Style};
+
+/// A small box of text shown next to the mouse, like a description of the monster or item under it.
+/// See Terminal::show_tooltip.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Tooltip {
+    pub text: String,
+    pub style: Style,
+    pub border: Option<BorderStyle>,
+    /// The widest the tooltip gets, border included. Longer text is wrapped.
+    pub max_width: u32,
+}
+
+impl Tooltip {
+    /// Create a new Tooltip with a single line border.
+    pub fn new(text: &str) -> Tooltip {
+        Tooltip {
+            text: text.to_string(),
+            style: Style::default(),
+            border: Some(BorderStyle::Single),
+            max_width: 30,
+        }
+    }
+
+    /// Return a new Tooltip with a changed style.
+    pub fn with_style(self, style: Style) -> Tooltip {
+        let mut new = self;
+        new.style = style;
+        new
+    }
+
+    /// Return a new Tooltip with a changed border, or no border.
+    pub fn with_border(self, border: Option<BorderStyle>) -> Tooltip {
+        let mut new = self;
+        new.border = border;
+        new
+    }
+
+    /// Lay out the tooltip for a mouse at point on a terminal with the given Dimensions and draw it on a new Panel.
+    /// It goes below and to the right of the point, flipping to the other side where it would leave the terminal.
+    pub fn panel(&self, point: Point, terminal_dims: Dimensions) -> OozeResult<Panel> {
+        let term_size = terminal_dims.term_size;
+        let edge = if self.border.is_some() { 1 } else { 0 };
+
+        let max_text_width = self
+            .max_width
+            .min(term_size.x)
+            .saturating_sub(2 * edge)
+            .max(1);
+        let lines = wrap_text(&self.text, max_text_width as usize);
+        let text_width = lines.iter().map(|l| l.chars().count()).max().unwrap_or(0) as u32;
+        let size = Point::new(
+            (text_width.max(1) + 2 * edge).min(term_size.x),
+            (lines.len() as u32 + 2 * edge).min(term_size.y),
+        );
+
+        let right_of = point.x + 1;
+        let x = if right_of + size.x <= term_size.x {
+            right_of
+        } else {
+            point.x.saturating_sub(size.x).min(term_size.x - size.x)
+        };
+        // below the point means its top row is the row under the point
+        let y = if point.y >= size.y {
+            point.y - size.y
+        } else {
+            (point.y + 1).min(term_size.y - size.y)
+        };
+
+        let mut panel = Panel::new(terminal_dims.copy_for_panel(size, Point::new(x, y)));
+        let (fg, bg) = self.style.colors(false);
+        let rect = panel.rect();
+        clear_rect(&mut panel, rect, bg)?;
+        if let Some(border) = &self.border {
+            panel.draw_border(rect, border, fg, bg)?;
+        }
+
+        let text_rect = Rect::new(
+            Point::new(edge, edge),
+            Point::new(
+                size.x.saturating_sub(2 * edge),
+                size.y.saturating_sub(2 * edge),
+            ),
+        );
+        for (row, line) in lines.iter().enumerate() {
+            print_in_rect(&mut panel, text_rect, row as u32, 0, line, fg, bg)?;
+        }
+
+        Ok(panel)
+    }
+}
+
+impl Terminal {
+    /// Show a tooltip next to point, above every Panel and dialog, replacing any tooltip already shown.
+    pub fn show_tooltip(&mut self, tooltip: &Tooltip, point: Point) -> OozeResult<()> {
+        let mut panel = tooltip.panel(point, self.dims)?;
+        panel.set_layer(self.top_layer() + 1);
+        self.tooltip = Some(panel);
+
+        Ok(())
+    }
+
+    /// Stop showing the tooltip.
+    pub fn hide_tooltip(&mut self) {
+        self.tooltip = None;
+    }
+}
@@ -0,0 +1,207 @@
+use std::any::Any;
+
+use crate::error::OozeResult;
+use crate::geometry::Rect;
+use crate::input::InputEvent;
+use crate::terminal::{Brush, Panel};
+
+/// What a widget did with an input event.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum WidgetEvent {
+    /// The widget didn't use the event.
+    Ignored,
+    /// The widget used the event, but nothing the game needs to know about happened.
+    Consumed,
+    /// The widget's value changed: a list selection moved, a checkbox toggled, text was typed.
+    Changed,
+    /// The widget was activated: a button pressed, a list item chosen, text entered.
+    Activated,
+}
+
+/// The colors a widget is drawn with, normally and while it has keyboard focus.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Style {
+    pub fg_color: [f32; 4],
+    pub bg_color: [f32; 4],
+    pub focus_fg_color: [f32; 4],
+    pub focus_bg_color: [f32; 4],
+}
+
+impl Style {
+    /// Create a new Style.
+    pub fn new(
+        fg_color: [f32; 4],
+        bg_color: [f32; 4],
+        focus_fg_color: [f32; 4],
+        focus_bg_color: [f32; 4],
+    ) -> Style {
+        Style {
+            fg_color,
+            bg_color,
+            focus_fg_color,
+            focus_bg_color,
+        }
+    }
+
+    /// The (fg, bg) colors to draw with.
+    pub fn colors(&self, focused: bool) -> ([f32; 4], [f32; 4]) {
+        if focused {
+            (self.focus_fg_color, self.focus_bg_color)
+        } else {
+            (self.fg_color, self.bg_color)
+        }
+    }
+}
+
+impl Default for Style {
+    /// White on black, black on white when focused.
+    fn default() -> Style {
+        Style::new(
+            [1.0, 1.0, 1.0, 1.0],
+            [0.0, 0.0, 0.0, 1.0],
+            [0.0, 0.0, 0.0, 1.0],
+            [1.0, 1.0, 1.0, 1.0],
+        )
+    }
+}
+
+/// A piece of UI that draws itself into a region of a Panel and reacts to input.
+/// Text is drawn one Glyph per character with the character as its sprite id, like Panel::print.
+pub trait Widget {
+    /// The region of the Panel this widget draws into.
+    fn rect(&self) -> Rect;
+
+    /// Move the widget to another region of its Panel.
+    fn set_rect(&mut self, rect: Rect);
+
+    /// Draw the widget into its region of the Panel.
+    fn draw(&self, panel: &mut Panel, focused: bool) -> OozeResult<()>;
+
+    /// React to an input event. Only called while the widget has focus.
+    fn handle_input(&mut self, _event: &InputEvent) -> WidgetEvent {
+        WidgetEvent::Ignored
+    }
+
+    /// Check if this widget can take keyboard focus.
+    fn focusable(&self) -> bool {
+        false
+    }
+
+    /// This widget as Any, so a Ui can hand out its concrete type.
+    fn as_any(&self) -> &dyn Any;
+
+    /// This widget as mutable Any, so a Ui can hand out its concrete type.
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+/// Fill a region of a Panel with blank cells in the given background color, clipped to the Panel.
+pub fn clear_rect(panel: &mut Panel, rect: Rect, bg_color: [f32; 4]) -> OozeResult<()> {
+    panel.fill_rect(rect, &Brush::new(" ", bg_color, bg_color))
+}
+
+/// Write text on a row of a region, counted from the top, starting column columns in.
+/// The text is cut off at the right edge of the region and nothing is drawn for rows outside of it.
+pub fn print_in_rect(
+    panel: &mut Panel,
+    rect: Rect,
+    row: u32,
+    column: u32,
+    text: &str,
+    fg_color: [f32; 4],
+    bg_color: [f32; 4],
+) -> OozeResult<()> {
+    if row >= rect.size.y || column >= rect.size.x {
+        return Ok(());
+    }
+    let y = rect.bottom_left.y + rect.size.y - 1 - row;
+    let room = (rect.size.x - column) as usize;
+    let text: String = text.chars().take(room).collect();
+
+    panel.print(rect.bottom_left.x + column, y, &text, fg_color, bg_color)
+}
+
+/// Split text into lines no wider than width, breaking between words where possible.
+/// Newlines in the text always start a new line.
+pub fn wrap_text(text: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    if width == 0 {
+        return lines;
+    }
+
+    for paragraph in text.split('\n') {
+        let mut line = String::new();
+        let mut line_length = 0;
+        for word in paragraph.split(' ') {
+            let mut word: Vec<char> = word.chars().collect();
+            // words longer than a whole line are broken up
+            while word.len() > width {
+                if line_length > 0 {
+                    lines.push(line);
+                    line = String::new();
+                    line_length = 0;
+                }
+                lines.push(word.drain(..width).collect());
+            }
+
+            let needed = if line_length == 0 {
+                word.len()
+            } else {
+                line_length + 1 + word.len()
+            };
+            if needed > width {
+                lines.push(line);
+                line = String::new();
+                line_length = 0;
+            }
+            if line_length > 0 {
+                line.push(' ');
+                line_length += 1;
+            }
+            line.extend(word.iter());
+            line_length += word.len();
+        }
+        lines.push(line);
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wrap_between_words() {
+        assert_eq!(
+            wrap_text("the quick brown fox", 10),
+            vec!["the quick", "brown fox"]
+        );
+        assert_eq!(wrap_text("fits", 4), vec!["fits"]);
+        assert_eq!(wrap_text("héllo wörld", 5), vec!["héllo", "wörld"]);
+    }
+
+    #[test]
+    fn wrap_breaks_up_long_words() {
+        assert_eq!(wrap_text("abcdefghij", 4), vec!["abcd", "efgh", "ij"]);
+        assert_eq!(wrap_text("abcdefgh", 4), vec!["abcd", "efgh"]);
+        assert_eq!(
+            wrap_text("hi abcdefghij yo", 4),
+            vec!["hi", "abcd", "efgh", "ij", "yo"]
+        );
+    }
+
+    #[test]
+    fn wrap_keeps_newlines() {
+        assert_eq!(wrap_text("a\n\nb", 10), vec!["a", "", "b"]);
+        assert_eq!(
+            wrap_text("one two\nthree", 3),
+            vec!["one", "two", "thr", "ee"]
+        );
+    }
+
+    #[test]
+    fn wrap_degenerate_widths_and_text() {
+        assert!(wrap_text("anything", 0).is_empty());
+        assert_eq!(wrap_text("", 5), vec![""]);
+        assert_eq!(wrap_text("a b", 1), vec!["a", "b"]);
+    }
+}